@@ -32,3 +32,10 @@ export const render_triangle = (
 
 export const delete_elem = (id: number) =>
   document.getElementById(`poly-${id}`)?.remove();
+
+export const set_view_box = (
+  x: number,
+  y: number,
+  width: number,
+  height: number
+) => SVG.setAttribute('viewBox', `${x} ${y} ${width} ${height}`);
@@ -0,0 +1,393 @@
+//! An unbounded world split into square chunks.  The triangles of each chunk are generated lazily
+//! as the camera moves over it, deterministically from the seed and the chunk's coordinates, so a
+//! chunk looks the same every time it is generated no matter which other chunks are loaded.
+//!
+//! Chunks are a lightweight backdrop rather than an extension of the canvas.  Each chain is grown
+//! with a plain random walk that only uses its triangle size, count, rotation settings, and colors,
+//! ignoring growth modes, fractal growth, flow fields, gap filling, subdivision, image colors,
+//! spawn points, territories, and leashes as well as the field's symmetry, wrapping, masks,
+//! keep-out rectangles, density map, and pointer repulsion.  Triangles are kept entirely inside of
+//! their chunk, so chunk borders show up as straight seams that no chain crosses.  Chunks don't
+//! change once generated since `generate` doesn't run on them, and their triangles live outside of
+//! the collision world so `triangle_at` and the spatial queries don't see them.
+
+use std::collections::HashMap;
+
+use ncollide2d::bounding_volume::{aabb::AABB, BoundingVolume};
+use ncollide2d::na::{Isometry2, Point2, Vector2};
+use ncollide2d::partitioning::{DBVTLeaf, BVH, DBVT};
+use rand::Rng;
+use rand_core::SeedableRng;
+use rand_pcg::Pcg32;
+
+use super::{
+    check_triangle_collision, deg_to_rad, delete_elem, get_triangle_bv, p2, render_triangle_array,
+    AABBQueryVisitor, Conf, TriangleBuf, PLACEMENT_ATTEMPTS,
+};
+
+/// Number of random positions tried when placing the first triangle of a chain in a chunk
+const CHUNK_SEED_ATTEMPTS: usize = 20;
+/// A chain stops growing in a chunk once this many of its placements have failed
+const CHUNK_MAX_FAILED_PLACEMENTS: usize = 100;
+
+/// `(x, y)` index of a chunk, with the chunk `(0, 0)` starting at the world origin
+pub type ChunkCoords = (i32, i32);
+
+/// The part of the world shown on the page
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    /// World coordinates of the top left corner of the viewport
+    pub position: Point2<f32>,
+    /// Magnification on top of the display scale
+    pub zoom: f32,
+    pub viewport_width: f32,
+    pub viewport_height: f32,
+}
+
+impl Camera {
+    /// Returns the area of the world that is visible in the viewport when the world is displayed
+    /// at `pixels_per_unit` before zooming
+    pub fn get_visible_bounds(&self, pixels_per_unit: f32) -> AABB<f32> {
+        let size =
+            Vector2::new(self.viewport_width, self.viewport_height) / (self.zoom * pixels_per_unit);
+        AABB::new(self.position, self.position + size)
+    }
+}
+
+struct Chunk {
+    /// Ids of the rendered elements of the chunk's triangles, which are in world coordinates
+    dom_ids: Vec<usize>,
+    /// Value of `ChunkedWorld::frame` the last time that the chunk was near the viewport
+    last_needed_frame: usize,
+}
+
+pub struct ChunkedWorld {
+    pub chunk_size: f32,
+    /// Chunks that aren't near the viewport are evicted, least recently seen first, once more than
+    /// this many are loaded
+    pub max_chunks: usize,
+    pub camera: Camera,
+    chunks: HashMap<ChunkCoords, Chunk>,
+    /// Incremented every time that the loaded chunks are updated
+    frame: usize,
+}
+
+impl ChunkedWorld {
+    pub fn new(chunk_size: f32, max_chunks: usize, camera: Camera) -> Self {
+        ChunkedWorld {
+            chunk_size,
+            max_chunks,
+            camera,
+            chunks: HashMap::new(),
+            frame: 0,
+        }
+    }
+
+    pub fn get_chunk_bounds(&self, (x, y): ChunkCoords) -> AABB<f32> {
+        let mins = p2(x as f32 * self.chunk_size, y as f32 * self.chunk_size);
+        AABB::new(mins, mins + Vector2::new(self.chunk_size, self.chunk_size))
+    }
+
+    /// Returns the coordinates of all chunks overlapping the viewport or within one chunk of it,
+    /// so that chunks are generated just before they scroll into view
+    fn get_needed_chunks(&self, pixels_per_unit: f32) -> Vec<ChunkCoords> {
+        let bounds = self
+            .camera
+            .get_visible_bounds(pixels_per_unit)
+            .loosened(self.chunk_size);
+        let to_chunk = |val: f32| (val / self.chunk_size).floor() as i32;
+        let mut needed = Vec::new();
+        for y in to_chunk(bounds.mins.y)..=to_chunk(bounds.maxs.y) {
+            for x in to_chunk(bounds.mins.x)..=to_chunk(bounds.maxs.x) {
+                needed.push((x, y));
+            }
+        }
+        needed
+    }
+
+    /// Generates and renders chunks that have come near the viewport and evicts the least recently
+    /// seen chunks that are no longer near it once more than `max_chunks` are loaded.  Chains are
+    /// grown using the settings in `confs`, one for each chain.
+    pub fn update(&mut self, confs: &[&Conf], pixels_per_unit: f32) {
+        self.frame += 1;
+        for coords in self.get_needed_chunks(pixels_per_unit) {
+            let bounds = self.get_chunk_bounds(coords);
+            let frame = self.frame;
+            self.chunks
+                .entry(coords)
+                .or_insert_with(|| Chunk {
+                    dom_ids: render_chunk(confs, coords, &bounds),
+                    last_needed_frame: frame,
+                })
+                .last_needed_frame = frame;
+        }
+
+        if self.chunks.len() <= self.max_chunks {
+            return;
+        }
+        let mut evictable: Vec<(ChunkCoords, usize)> = self
+            .chunks
+            .iter()
+            .filter(|(_, chunk)| chunk.last_needed_frame != self.frame)
+            .map(|(coords, chunk)| (*coords, chunk.last_needed_frame))
+            .collect();
+        evictable.sort_unstable_by_key(|&(coords, last_needed_frame)| (last_needed_frame, coords));
+        let evict_count = (self.chunks.len() - self.max_chunks).min(evictable.len());
+        for (coords, _) in &evictable[..evict_count] {
+            if let Some(chunk) = self.chunks.remove(coords) {
+                delete_chunk_elems(&chunk);
+            }
+        }
+    }
+
+    /// Removes all loaded chunks from the DOM
+    pub fn clear(&mut self) {
+        for (_, chunk) in self.chunks.drain() {
+            delete_chunk_elems(&chunk);
+        }
+    }
+}
+
+fn delete_chunk_elems(chunk: &Chunk) {
+    for &dom_id in &chunk.dom_ids {
+        delete_elem(dom_id);
+    }
+}
+
+/// Mixes the bits of `val` so that nearby inputs produce unrelated outputs (SplitMix64)
+fn mix_bits(mut val: u64) -> u64 {
+    val = val.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    val = (val ^ (val >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    val = (val ^ (val >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    val ^ (val >> 31)
+}
+
+fn get_chunk_rng(seed: f64, (x, y): ChunkCoords) -> Pcg32 {
+    let coords = ((x as u32 as u64) << 32) | y as u32 as u64;
+    Pcg32::seed_from_u64(mix_bits(seed.to_bits() ^ mix_bits(coords)))
+}
+
+fn render_chunk(confs: &[&Conf], coords: ChunkCoords, bounds: &AABB<f32>) -> Vec<usize> {
+    generate_chunk(confs, coords, bounds)
+        .into_iter()
+        .map(|(chain_ix, triangle)| {
+            let conf = confs[chain_ix];
+            render_triangle_array(&triangle, &conf.triangle_color, &conf.triangle_border_color)
+        })
+        .collect()
+}
+
+/// Grows one chain for each of `confs` inside of the chunk at `coords` with bounds `bounds`,
+/// returning the `(chain_ix, triangle)` of every triangle placed.  Chains random walk like they do
+/// on the canvas, branching from a random earlier triangle when they get stuck, until they have
+/// `triangle_count` triangles.  Triangles are kept entirely inside of the chunk so that chunks
+/// never overlap each other.
+pub fn generate_chunk(
+    confs: &[&Conf],
+    coords: ChunkCoords,
+    bounds: &AABB<f32>,
+) -> Vec<(usize, TriangleBuf)> {
+    let mut rng = get_chunk_rng(confs.first().map_or(0., |conf| conf.prng_seed), coords);
+    let mut placed: Vec<(usize, TriangleBuf)> = Vec::new();
+    // leaves are `(chain_ix, index into placed, 0)`, matching the layout of the canvas' world
+    let mut world: DBVT<f32, (usize, usize, usize), AABB<f32>> = DBVT::new();
+    let fits = |world: &DBVT<_, _, _>, placed: &[(usize, TriangleBuf)], triangle: &TriangleBuf| {
+        let bv = get_triangle_bv(triangle);
+        if !bounds.contains(&bv) {
+            return false;
+        }
+        let mut nearby = Vec::new();
+        world.visit(&mut AABBQueryVisitor {
+            aabb: &bv,
+            results: &mut nearby,
+        });
+        !nearby
+            .iter()
+            .any(|&(_, ix, _)| check_triangle_collision(triangle, &placed[ix].1))
+    };
+    let place =
+        |world: &mut DBVT<_, _, _>, placed: &mut Vec<_>, chain_ix, triangle: TriangleBuf| {
+            world.insert(DBVTLeaf::new(
+                get_triangle_bv(&triangle),
+                (chain_ix, placed.len(), 0),
+            ));
+            placed.push((chain_ix, triangle));
+        };
+
+    for (chain_ix, conf) in confs.iter().enumerate() {
+        let (triangle_offset_x, triangle_offset_y) = conf.get_base_triangle_offsets();
+        let base_triangle_coords = [
+            Point2::origin(),
+            p2(-triangle_offset_x, triangle_offset_y),
+            p2(triangle_offset_x, triangle_offset_y),
+        ];
+        let build_triangle = |origin: Point2<f32>, rotation: f32| -> TriangleBuf {
+            let isometry = Isometry2::new(origin.coords, rotation);
+            base_triangle_coords.map(|pt| isometry * pt)
+        };
+
+        // `(triangle, rotation)` of each triangle in the chain
+        let mut chain: Vec<(TriangleBuf, f32)> = Vec::new();
+        let inset = bounds.tightened(conf.triangle_size.min(bounds.half_extents().x));
+        for _ in 0..CHUNK_SEED_ATTEMPTS {
+            let origin = p2(
+                rng.gen_range(inset.mins.x, inset.maxs.x + 0.001),
+                rng.gen_range(inset.mins.y, inset.maxs.y + 0.001),
+            );
+            let rotation = rng.gen_range(0.0, std::f32::consts::PI / 2.0);
+            let triangle = build_triangle(origin, rotation);
+            if fits(&world, &placed, &triangle) {
+                chain.push((triangle, rotation));
+                place(&mut world, &mut placed, chain_ix, triangle);
+                break;
+            }
+        }
+        if chain.is_empty() {
+            continue;
+        }
+
+        let mut last_ix = 0;
+        let mut failed_placements = 0;
+        while chain.len() < conf.triangle_count && failed_placements < CHUNK_MAX_FAILED_PLACEMENTS {
+            let (last_triangle, rotation) = chain[last_ix];
+            let (vertex_ix, rot_offset) = if rng.gen_range(0, 2) == 0 {
+                (1, deg_to_rad(conf.rotation_offset))
+            } else {
+                (2, deg_to_rad(-conf.rotation_offset))
+            };
+
+            let mut placement = None;
+            for _ in 0..PLACEMENT_ATTEMPTS {
+                let proposed_rotation = rotation
+                    + rot_offset
+                    + rng.gen_range(-conf.max_rotation_rads, conf.max_rotation_rads + 0.00001);
+                let triangle = build_triangle(last_triangle[vertex_ix], proposed_rotation);
+                if fits(&world, &placed, &triangle) {
+                    placement = Some(triangle);
+                    break;
+                }
+            }
+
+            match placement {
+                Some(triangle) => {
+                    chain.push((triangle, rotation + rot_offset));
+                    place(&mut world, &mut placed, chain_ix, triangle);
+                    last_ix = chain.len() - 1;
+                }
+                None => {
+                    failed_placements += 1;
+                    last_ix = rng.gen_range(0, chain.len());
+                }
+            }
+        }
+    }
+
+    placed
+}
+
+#[cfg(test)]
+fn build_test_conf() -> Conf {
+    Conf {
+        prng_seed: 9209.2338,
+        canvas_width: 800,
+        canvas_height: 600,
+        triangle_size: 12.25,
+        triangle_count: 30,
+        max_rotation_rads: 0.5,
+        triangle_color: String::new(),
+        triangle_border_color: String::new(),
+        rotation_offset: 60.0,
+        generation_rate: 26.0,
+        fractal_growth: None,
+        flow_field: None,
+        growth_mode: super::GrowthMode::RandomWalk,
+        gap_fill: None,
+        subdivision: None,
+        image_colors: None,
+        spawn: None,
+        territory: None,
+        leash: None,
+    }
+}
+
+#[test]
+fn chunks_are_deterministic_and_contained() {
+    let conf = build_test_conf();
+    let confs = [&conf, &conf];
+    let world = ChunkedWorld::new(
+        200.,
+        4,
+        Camera {
+            position: Point2::origin(),
+            zoom: 1.,
+            viewport_width: 100.,
+            viewport_height: 100.,
+        },
+    );
+    assert_eq!(world.get_needed_chunks(1.).len(), 9);
+
+    assert_eq!(world.get_needed_chunks(0.4).len(), 16);
+
+    let bounds = world.get_chunk_bounds((-3, 2));
+    assert_eq!(bounds.mins, p2(-600., 400.));
+    let chunk = generate_chunk(&confs, (-3, 2), &bounds);
+    assert!(chunk.len() > 30);
+    assert_eq!(chunk, generate_chunk(&confs, (-3, 2), &bounds));
+    assert_ne!(chunk, generate_chunk(&confs, (-3, 3), &bounds));
+    for (i, (_, triangle)) in chunk.iter().enumerate() {
+        assert!(bounds.contains(&get_triangle_bv(triangle)));
+        for (_, other) in &chunk[..i] {
+            assert!(!check_triangle_collision(triangle, other));
+        }
+    }
+}
+
+#[test]
+fn neighboring_chunks_meet_without_overlapping() {
+    let mut conf = build_test_conf();
+    conf.triangle_count = 150;
+    let confs = [&conf, &conf];
+    let world = ChunkedWorld::new(
+        150.,
+        4,
+        Camera {
+            position: Point2::origin(),
+            zoom: 1.,
+            viewport_width: 100.,
+            viewport_height: 100.,
+        },
+    );
+    let neighbors = [(0, 0), (1, 0), (0, 1), (1, 1)];
+    let generate =
+        |coords: ChunkCoords| generate_chunk(&confs, coords, &world.get_chunk_bounds(coords));
+
+    // chunks don't depend on which of their neighbors were generated before them
+    let chunks: Vec<Vec<(usize, TriangleBuf)>> =
+        neighbors.iter().map(|&coords| generate(coords)).collect();
+    for (&coords, chunk) in neighbors.iter().zip(&chunks).rev() {
+        assert_eq!(generate(coords), *chunk);
+    }
+
+    // every pair of chunks shares a border or a corner, and triangles come up to the borders from
+    // both sides without crossing them
+    let border_x = world.get_chunk_bounds((1, 0)).mins.x;
+    let near_border = |chunk: &[(usize, TriangleBuf)]| {
+        chunk.iter().any(|(_, triangle)| {
+            let bv = get_triangle_bv(triangle);
+            (bv.mins.x - border_x).abs() < conf.triangle_size
+                || (bv.maxs.x - border_x).abs() < conf.triangle_size
+        })
+    };
+    assert!(near_border(&chunks[0]) && near_border(&chunks[1]));
+    for (i, (&coords, chunk)) in neighbors.iter().zip(&chunks).enumerate() {
+        let bounds = world.get_chunk_bounds(coords);
+        for (_, triangle) in chunk {
+            assert!(bounds.contains(&get_triangle_bv(triangle)));
+            for other_chunk in &chunks[..i] {
+                for (_, other) in other_chunk {
+                    assert!(!check_triangle_collision(triangle, other));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Exports the rendered field as a standalone SVG or PNG image.  When the field wraps around at its
+//! edges, the exported images tile seamlessly and can be used as repeating backgrounds.
+
+use std::fmt::Write;
+
+use super::{distance_to_segment, p2, point_in_triangle, TriangleBuf};
+
+/// A triangle as it was rendered, with the colors it was rendered with
+pub struct RenderedTriangle<'a> {
+    pub geometry: &'a TriangleBuf,
+    pub color: &'a str,
+    pub border_color: &'a str,
+}
+
+/// Builds an SVG image of size `width` by `height` containing `triangles` with their coordinates
+/// multiplied by `scale`, drawn in order with the same styles that they are rendered with on the
+/// page.  Parts of triangles outside of the image are clipped.
+pub fn build_svg(
+    width: usize,
+    height: usize,
+    scale: f32,
+    triangles: &[RenderedTriangle],
+) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} \
+         {1}\">",
+        width, height
+    );
+    for triangle in triangles {
+        let [p1, p2, p3] = triangle.geometry.map(|pt| pt * scale);
+        write!(
+            svg,
+            "<polygon points=\"{},{} {},{} {},{}\" style=\"fill:{};stroke:{};stroke-width:1\"/>",
+            p1.x, p1.y, p2.x, p2.y, p3.x, p3.y, triangle.color, triangle.border_color
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Parses a CSS color in `rgb(r, g, b)`, `#rrggbb`, or `#rgb` form
+pub fn parse_color(color: &str) -> Option<[u8; 3]> {
+    let color = color.trim();
+    if let Some(hex) = color.strip_prefix('#') {
+        let channel = |ix: usize, len: usize| {
+            u8::from_str_radix(hex.get(ix * len..(ix + 1) * len)?, 16)
+                .ok()
+                .map(|val| if len == 1 { val * 17 } else { val })
+        };
+        let len = match hex.len() {
+            3 => 1,
+            6 => 2,
+            _ => return None,
+        };
+        return Some([channel(0, len)?, channel(1, len)?, channel(2, len)?]);
+    }
+
+    let channels = color.strip_prefix("rgb(")?.strip_suffix(')')?;
+    let mut channels = channels
+        .split(',')
+        .map(|channel| channel.trim().parse::<u8>());
+    let rgb = [
+        channels.next()?.ok()?,
+        channels.next()?.ok()?,
+        channels.next()?.ok()?,
+    ];
+    if channels.next().is_some() {
+        return None;
+    }
+    Some(rgb)
+}
+
+/// Rasterizes `triangles` with their coordinates multiplied by `scale` into a transparent RGBA
+/// image of size `width` by `height` and encodes it as a PNG.  Pixels are filled if their centers
+/// are inside of a triangle, and pixels within half of `stroke_width` of an edge are drawn with the
+/// border color to match the stroke of rendered triangles.
+pub fn build_png(
+    width: usize,
+    height: usize,
+    scale: f32,
+    stroke_width: f32,
+    triangles: &[RenderedTriangle],
+) -> Result<Vec<u8>, String> {
+    let mut pixels = vec![0u8; width * height * 4];
+    for triangle in triangles {
+        let geometry = &triangle.geometry.map(|pt| pt * scale);
+        let (fill, border) = (
+            parse_color(triangle.color),
+            parse_color(triangle.border_color),
+        );
+        let (mins, maxs) = super::bounds(geometry[0], geometry[1], geometry[2]);
+        let x_range = (mins.x - 1.).floor().max(0.) as usize
+            ..((maxs.x + 1.).ceil().max(0.) as usize).min(width);
+        let y_range = (mins.y - 1.).floor().max(0.) as usize
+            ..((maxs.y + 1.).ceil().max(0.) as usize).min(height);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let pt = p2(x as f32 + 0.5, y as f32 + 0.5);
+                let edge_distance = distance_to_segment(pt, geometry[0], geometry[1])
+                    .min(distance_to_segment(pt, geometry[1], geometry[2]))
+                    .min(distance_to_segment(pt, geometry[2], geometry[0]));
+                let color = if edge_distance <= stroke_width / 2. && border.is_some() {
+                    border
+                } else if point_in_triangle(pt, geometry) {
+                    fill
+                } else {
+                    None
+                };
+
+                if let Some([r, g, b]) = color {
+                    let ix = (y * width + x) * 4;
+                    pixels[ix..ix + 4].copy_from_slice(&[r, g, b, 255]);
+                }
+            }
+        }
+    }
+
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut bytes, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .map_err(|err| format!("Failed to write PNG header: {}", err))?;
+        writer
+            .write_image_data(&pixels)
+            .map_err(|err| format!("Failed to encode PNG: {}", err))?;
+    }
+    Ok(bytes)
+}
+
+#[test]
+fn svg_and_png_export() {
+    assert_eq!(parse_color("rgb(81, 12, 84)"), Some([81, 12, 84]));
+    assert_eq!(parse_color("#0fbee6"), Some([15, 190, 230]));
+    assert_eq!(parse_color("#f0a"), Some([255, 0, 170]));
+    assert_eq!(parse_color(""), None);
+    assert_eq!(parse_color("rgb(1, 2, 3, 4)"), None);
+
+    // a triangle crossing the right edge and its copy wrapped around to the left edge
+    let triangle = [p2(15., 2.), p2(25., 2.), p2(15., 12.)];
+    let wrapped = [p2(-5., 2.), p2(5., 2.), p2(-5., 12.)];
+    let triangles = [
+        RenderedTriangle {
+            geometry: &triangle,
+            color: "rgb(255, 0, 0)",
+            border_color: "rgb(0, 0, 255)",
+        },
+        RenderedTriangle {
+            geometry: &wrapped,
+            color: "rgb(255, 0, 0)",
+            border_color: "rgb(0, 0, 255)",
+        },
+    ];
+
+    let svg = build_svg(20, 20, 1., &triangles);
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("points=\"15,2 25,2 15,12\""));
+    assert_eq!(svg.matches("<polygon").count(), 2);
+
+    assert!(build_svg(40, 40, 2., &triangles).contains("points=\"30,4 50,4 30,24\""));
+
+    let image =
+        super::image::Image::decode_png(&build_png(20, 20, 1., 1., &triangles).unwrap()).unwrap();
+    assert_eq!((image.width, image.height), (20, 20));
+    assert_eq!(image.get_pixel(17, 4), [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(1, 4), [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(16, 1), [0, 0, 255, 255]);
+    assert_eq!(image.get_pixel(10, 10), [0, 0, 0, 0]);
+}
@@ -0,0 +1,294 @@
+//! Images decoded in the engine and mapped onto the canvas so that they can be sampled at points
+//! where triangles are placed.
+
+use ncollide2d::bounding_volume::aabb::AABB;
+use ncollide2d::na::Point2;
+
+use super::TriangleBuf;
+
+/// Number of rows of sample points used when averaging an image over a triangle, which samples
+/// the centroids of `AREA_SAMPLE_ROWS²` equal sub-triangles
+const AREA_SAMPLE_ROWS: usize = 4;
+
+/// An RGBA image with 8 bits per channel
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+    /// Decodes a PNG of any color type and bit depth into an RGBA image.
+    pub fn decode_png(bytes: &[u8]) -> Result<Self, String> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder
+            .read_info()
+            .map_err(|err| format!("Failed to read PNG header: {}", err))?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buf)
+            .map_err(|err| format!("Failed to decode PNG: {}", err))?;
+        let data = &buf[..info.buffer_size()];
+
+        let pixels: Vec<[u8; 4]> = match info.color_type {
+            png::ColorType::Grayscale => data.iter().map(|&l| [l, l, l, 255]).collect(),
+            png::ColorType::GrayscaleAlpha => data
+                .chunks_exact(2)
+                .map(|c| [c[0], c[0], c[0], c[1]])
+                .collect(),
+            png::ColorType::Rgb => data
+                .chunks_exact(3)
+                .map(|c| [c[0], c[1], c[2], 255])
+                .collect(),
+            png::ColorType::Rgba => data
+                .chunks_exact(4)
+                .map(|c| [c[0], c[1], c[2], c[3]])
+                .collect(),
+            png::ColorType::Indexed => return Err("Failed to expand indexed PNG".into()),
+        };
+
+        Ok(Image {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        self.pixels[y * self.width + x]
+    }
+}
+
+/// Returns the perceived brightness of `pixel` from 0 (black) to 1 (white), treating transparent
+/// areas as if they were composited over white
+pub fn get_brightness(pixel: [u8; 4]) -> f32 {
+    let [r, g, b, a] = pixel;
+    let luma = (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.;
+    let alpha = a as f32 / 255.;
+    luma * alpha + (1. - alpha)
+}
+
+/// An image stretched to cover a rectangle of the canvas
+#[derive(Clone, Debug)]
+pub struct CanvasImage {
+    pub image: Image,
+    pub bounds: AABB<f32>,
+}
+
+impl CanvasImage {
+    /// Returns the pixel of the image covering `pt`, or `None` if the point is outside of the image.
+    pub fn sample(&self, pt: Point2<f32>) -> Option<[u8; 4]> {
+        let (mins, maxs) = (self.bounds.mins, self.bounds.maxs);
+        if pt.x < mins.x || pt.x >= maxs.x || pt.y < mins.y || pt.y >= maxs.y {
+            return None;
+        }
+
+        let u = (pt.x - mins.x) / (maxs.x - mins.x);
+        let v = (pt.y - mins.y) / (maxs.y - mins.y);
+        let x = ((u * self.image.width as f32) as usize).min(self.image.width - 1);
+        let y = ((v * self.image.height as f32) as usize).min(self.image.height - 1);
+        Some(self.image.get_pixel(x, y))
+    }
+
+    /// Returns the brightness of the image at `pt`, with areas outside of the image treated as white.
+    pub fn get_brightness(&self, pt: Point2<f32>) -> f32 {
+        self.sample(pt).map_or(1., get_brightness)
+    }
+
+    /// Returns the average color of the image at the centroids of equal sub-triangles covering
+    /// `triangle`, weighted by alpha.  Returns `None` if the triangle is entirely outside of the
+    /// image or over fully transparent pixels.
+    pub fn average_over_triangle(&self, triangle: &TriangleBuf) -> Option<[u8; 3]> {
+        let [a, b, c] = *triangle;
+        let rows = AREA_SAMPLE_ROWS as f32;
+        let barycentric_to_point = |u: f32, v: f32| a + (b - a) * (u / rows) + (c - a) * (v / rows);
+
+        let mut sums = [0f32; 3];
+        let mut total_weight = 0.;
+        let mut add_sample = |pt: Point2<f32>| {
+            if let Some([r, g, b, a]) = self.sample(pt) {
+                let weight = a as f32;
+                sums[0] += r as f32 * weight;
+                sums[1] += g as f32 * weight;
+                sums[2] += b as f32 * weight;
+                total_weight += weight;
+            }
+        };
+        for i in 0..AREA_SAMPLE_ROWS {
+            for j in 0..AREA_SAMPLE_ROWS - i {
+                let (u, v) = (i as f32, j as f32);
+                add_sample(barycentric_to_point(u + 1. / 3., v + 1. / 3.));
+                if i + j + 1 < AREA_SAMPLE_ROWS {
+                    add_sample(barycentric_to_point(u + 2. / 3., v + 2. / 3.));
+                }
+            }
+        }
+
+        if total_weight == 0. {
+            return None;
+        }
+        Some(sums.map(|sum| (sum / total_weight).round() as u8))
+    }
+}
+
+/// Builds a palette of up to `color_count` colors representing the opaque pixels of `image` using
+/// median cut: the pixels are repeatedly split at the median of the channel with the largest range
+/// and each resulting bucket is averaged.
+pub fn build_palette(image: &Image, color_count: usize) -> Vec<[u8; 3]> {
+    let pixels: Vec<[u8; 3]> = image
+        .pixels
+        .iter()
+        .filter(|[_, _, _, a]| *a > 0)
+        .map(|[r, g, b, _]| [*r, *g, *b])
+        .collect();
+    if pixels.is_empty() || color_count == 0 {
+        return Vec::new();
+    }
+
+    let get_widest_channel = |bucket: &[[u8; 3]]| -> (usize, u8) {
+        (0..3)
+            .map(|channel| {
+                let min = bucket.iter().map(|px| px[channel]).min().unwrap();
+                let max = bucket.iter().map(|px| px[channel]).max().unwrap();
+                (channel, max - min)
+            })
+            .max_by_key(|(_, range)| *range)
+            .unwrap()
+    };
+
+    let mut buckets: Vec<Vec<[u8; 3]>> = vec![pixels];
+    while buckets.len() < color_count {
+        let (bucket_ix, (channel, range)) = buckets
+            .iter()
+            .enumerate()
+            .map(|(ix, bucket)| (ix, get_widest_channel(bucket)))
+            .max_by_key(|(_, (_, range))| *range)
+            .unwrap();
+        if range == 0 {
+            break;
+        }
+
+        let mut bucket = buckets.swap_remove(bucket_ix);
+        bucket.sort_unstable_by_key(|px| px[channel]);
+        let upper = bucket.split_off(bucket.len() / 2);
+        buckets.push(bucket);
+        buckets.push(upper);
+    }
+
+    buckets
+        .iter()
+        .map(|bucket| {
+            let mut sums = [0u64; 3];
+            for px in bucket {
+                for channel in 0..3 {
+                    sums[channel] += px[channel] as u64;
+                }
+            }
+            sums.map(|sum| (sum / bucket.len() as u64) as u8)
+        })
+        .collect()
+}
+
+/// Returns the color in `palette` closest to `color`
+pub fn quantize(color: [u8; 3], palette: &[[u8; 3]]) -> [u8; 3] {
+    let distance = |other: &[u8; 3]| -> i32 {
+        (0..3)
+            .map(|channel| (color[channel] as i32 - other[channel] as i32).pow(2))
+            .sum()
+    };
+    palette
+        .iter()
+        .min_by_key(|other| distance(other))
+        .copied()
+        .unwrap_or(color)
+}
+
+/// Mixes `color` toward white by `amount` if it is positive or toward black if it is negative
+pub fn shade(color: [u8; 3], amount: f32) -> [u8; 3] {
+    let amount = amount.clamp(-1., 1.);
+    let target = if amount > 0. { 255. } else { 0. };
+    color.map(|channel| {
+        let channel = channel as f32;
+        (channel + (target - channel) * amount.abs()).round() as u8
+    })
+}
+
+pub fn format_rgb([r, g, b]: [u8; 3]) -> String {
+    format!("rgb({}, {}, {})", r, g, b)
+}
+
+#[test]
+fn png_decoding_and_sampling() {
+    // 2x2 grayscale image: black, white / gray, transparent black
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut bytes, 2, 2);
+        encoder.set_color(png::ColorType::GrayscaleAlpha);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&[0, 255, 255, 255, 128, 255, 0, 0])
+            .unwrap();
+    }
+
+    let image = Image::decode_png(&bytes).unwrap();
+    assert_eq!((image.width, image.height), (2, 2));
+    assert_eq!(image.get_pixel(1, 0), [255, 255, 255, 255]);
+
+    let canvas_image = CanvasImage {
+        image,
+        bounds: AABB::new(Point2::new(100., 100.), Point2::new(300., 300.)),
+    };
+    assert_eq!(canvas_image.get_brightness(Point2::new(150., 150.)), 0.);
+    assert_eq!(canvas_image.get_brightness(Point2::new(250., 150.)), 1.);
+    assert!((canvas_image.get_brightness(Point2::new(150., 250.)) - 0.5).abs() < 0.01);
+    assert_eq!(canvas_image.get_brightness(Point2::new(250., 250.)), 1.);
+    assert_eq!(canvas_image.sample(Point2::new(50., 150.)), None);
+    assert!(Image::decode_png(&[1, 2, 3]).is_err());
+}
+
+#[test]
+fn palette_and_triangle_colors() {
+    // left half red, right half blue, with a darker blue column
+    let mut pixels = Vec::new();
+    for _ in 0..4 {
+        pixels.extend_from_slice(&[
+            [255, 0, 0, 255],
+            [255, 0, 0, 255],
+            [0, 0, 255, 255],
+            [0, 0, 200, 255],
+        ]);
+    }
+    let image = Image {
+        width: 4,
+        height: 4,
+        pixels,
+    };
+
+    let mut palette = build_palette(&image, 2);
+    palette.sort();
+    assert_eq!(palette, vec![[0, 0, 227], [255, 0, 0]]);
+    assert_eq!(quantize([200, 30, 30], &palette), [255, 0, 0]);
+    assert_eq!(shade([100, 200, 0], 0.5), [178, 228, 128]);
+    assert_eq!(shade([100, 200, 0], -0.5), [50, 100, 0]);
+    assert_eq!(format_rgb([1, 2, 3]), "rgb(1, 2, 3)");
+
+    let canvas_image = CanvasImage {
+        image,
+        bounds: AABB::new(Point2::new(0., 0.), Point2::new(40., 40.)),
+    };
+    let left = [
+        Point2::new(1., 1.),
+        Point2::new(19., 1.),
+        Point2::new(1., 39.),
+    ];
+    assert_eq!(canvas_image.average_over_triangle(&left), Some([255, 0, 0]));
+    let outside = [
+        Point2::new(50., 50.),
+        Point2::new(60., 50.),
+        Point2::new(50., 60.),
+    ];
+    assert_eq!(canvas_image.average_over_triangle(&outside), None);
+}
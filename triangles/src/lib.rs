@@ -1,19 +1,34 @@
-#![allow(invalid_value)]
-
 use std::f32;
 use std::mem;
 use std::panic;
 use std::ptr;
-use std::usize;
+use std::rc::Rc;
 
 use ncollide2d::bounding_volume::{aabb::AABB, BoundingVolume};
-use ncollide2d::na::{Isometry2, Point2, Vector2};
+use ncollide2d::na::{self, Isometry2, Point2, Vector2};
 use ncollide2d::partitioning::{DBVTLeaf, DBVTLeafId, VisitStatus, Visitor, BVH, DBVT};
+use ncollide2d::query::PointQuery;
+use rand::seq::SliceRandom;
 use rand::Rng;
 use rand_core::SeedableRng;
 use rand_pcg::Pcg32;
 use wasm_bindgen::prelude::*;
 
+mod chunks;
+mod export;
+mod image;
+mod mask;
+mod noise;
+mod penrose;
+mod text;
+
+use chunks::{Camera, ChunkedWorld};
+use image::{build_palette, format_rgb, quantize, shade, CanvasImage, Image};
+pub use mask::FillRule;
+use mask::Mask;
+use noise::PerlinNoise;
+
+#[cfg(not(test))]
 #[wasm_bindgen(raw_module = "../src/triangleHooks.tsx")]
 extern "C" {
     #[allow(clippy::too_many_arguments)]
@@ -28,13 +43,75 @@ extern "C" {
         border_color: &str,
     ) -> usize;
     pub fn delete_elem(elem_id: usize);
+    pub fn set_view_box(x: f32, y: f32, width: f32, height: f32);
+}
+
+/// Stand-ins for the page's hooks so that tests can build a field natively
+#[cfg(test)]
+mod test_hooks {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    static NEXT_ELEM_ID: AtomicUsize = AtomicUsize::new(0);
+    /// Held by tests that build a field, since the field is global
+    pub static FIELD_LOCK: Mutex<()> = Mutex::new(());
+    /// Ids of the deleted elements in the order that they were deleted
+    pub static DELETED_ELEMS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_triangle(
+        _x1: f32,
+        _y1: f32,
+        _x2: f32,
+        _y2: f32,
+        _x3: f32,
+        _y3: f32,
+        _color: &str,
+        _border_color: &str,
+    ) -> usize {
+        NEXT_ELEM_ID.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub fn delete_elem(elem_id: usize) {
+        DELETED_ELEMS
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(elem_id);
+    }
+
+    pub fn set_view_box(_x: f32, _y: f32, _width: f32, _height: f32) {}
 }
+#[cfg(test)]
+use test_hooks::{delete_elem, render_triangle, set_view_box, DELETED_ELEMS, FIELD_LOCK};
 
 type TriangleBuf = [Point2<f32>; 3];
 
 const PLACEMENT_ATTEMPTS: usize = 5;
 const PLACEMENT_BAILOUT_THRESHOLD: usize = 1000;
+/// Maximum number of failed placement origins that are retried during a gap-filling pass
+const GAP_FILL_MAX_REGIONS: usize = 24;
+/// Vertices of Penrose tiles closer than this fraction of `triangle_size` are considered coincident
+const PENROSE_VERTEX_EPSILON: f32 = 0.01;
+/// Number of random points tried when looking for one inside of a mask
+const MAX_MASK_SAMPLES: usize = 1000;
+/// Number of random spots on the canvas tried when looking for a spot to seed a chain at
+const RANDOM_SEED_ATTEMPTS: usize = 1000;
+/// Number of random spots tried inside of a chain's spawn region before searching around it
+const SPAWN_REGION_ATTEMPTS: usize = 50;
+/// Number of spots tried when searching outward from a chain's seed position for a free spot
+const SPAWN_SEARCH_ATTEMPTS: usize = 200;
+/// Number of spots tried when searching outward from the point that a chain is spawned at with
+/// `spawn_chain`, which is kept small so that chains only spawn close to where they're placed
+const SPAWN_CHAIN_SEARCH_ATTEMPTS: usize = 10;
+/// Distance, as a fraction of the triangle size, that each spawn search attempt moves outward
+const SPAWN_SEARCH_STEP: f32 = 0.5;
+/// Number of permanent chains, which are followed by any chains spawned with `spawn_chain`
 const CHAIN_COUNT: usize = 3;
+const DEFAULT_MAX_SPAWNED_CHAINS: usize = 8;
+const DEFAULT_MAX_SPAWNED_TRIANGLES: usize = 400;
+/// Smallest zoom that the camera of an infinite world can be set to, which limits how many chunks
+/// can be visible at once
+const MIN_CAMERA_ZOOM: f32 = 0.25;
 
 #[derive(Clone)]
 pub struct Conf {
@@ -48,9 +125,540 @@ pub struct Conf {
     pub triangle_border_color: String,
     pub rotation_offset: f32,
     pub generation_rate: f32,
+    pub fractal_growth: Option<FractalGrowthConf>,
+    pub flow_field: Option<FlowFieldConf>,
+    pub growth_mode: GrowthMode,
+    pub gap_fill: Option<GapFillConf>,
+    pub subdivision: Option<SubdivisionConf>,
+    pub image_colors: Option<ImageColorConf>,
+    /// Where the chain places its first triangle and restarts from when it has no triangles left.
+    /// If unset, it starts at a random spot on the canvas.
+    pub spawn: Option<Spawn>,
+    /// If set, the chain's triangles must lie entirely inside of this region.  Only the chain's own
+    /// triangles are checked, not their symmetric or wrapped copies.
+    pub territory: Option<Rc<Mask>>,
+    /// If set, the chain's triangles must lie entirely inside of this circle
+    pub leash: Option<Leash>,
+}
+
+#[derive(Clone)]
+pub enum Spawn {
+    /// The chain grows off of a seed triangle with its tip at `position`, rotated clockwise by
+    /// `rotation` radians from pointing up
+    Point {
+        position: Point2<f32>,
+        rotation: f32,
+    },
+    /// The first triangle is placed at a random spot inside of the region with a random rotation
+    Region(Rc<Mask>),
+}
+
+/// A circle that a chain is kept inside of, usually centered on the point it was seeded at
+#[derive(Clone, Copy, Debug)]
+pub struct Leash {
+    pub center: Point2<f32>,
+    pub radius: f32,
+}
+
+impl Leash {
+    pub fn contains_triangle(&self, triangle: &TriangleBuf) -> bool {
+        // circles are convex, so a triangle is inside if all of its vertices are
+        triangle
+            .iter()
+            .all(|pt| (pt - self.center).norm_squared() <= self.radius * self.radius)
+    }
+
+    pub fn get_bounds(&self) -> AABB<f32> {
+        let extents = Vector2::new(self.radius, self.radius);
+        AABB::new(self.center - extents, self.center + extents)
+    }
+}
+
+/// Where in a triangle's area a source image is sampled to determine its color
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ColorSampling {
+    /// Use the color of the pixel under the triangle's centroid
+    Centroid,
+    /// Average the colors of the pixels covered by the triangle
+    AreaAverage,
+}
+
+/// Settings for coloring each triangle from a source image so that chains gradually paint the
+/// image as they grow.  Triangles outside of the image or over transparent pixels use the chain's
+/// colors from `Conf`.
+#[derive(Clone)]
+pub struct ImageColorConf {
+    pub image: Rc<CanvasImage>,
+    pub sampling: ColorSampling,
+    /// If set, sampled colors are snapped to the closest color in this palette
+    pub palette: Option<Vec<[u8; 3]>>,
+    /// Borders are the fill color mixed toward white by this amount if it is positive or toward
+    /// black if it is negative, from -1 to 1
+    pub border_shade: f32,
+}
+
+impl ImageColorConf {
+    /// Returns the `(fill, border)` colors for `triangle`, or `None` if the image doesn't cover it
+    pub fn get_colors(&self, triangle: &TriangleBuf) -> Option<(String, String)> {
+        let color = match self.sampling {
+            ColorSampling::Centroid => {
+                let [r, g, b, a] = self.image.sample(centroid(triangle))?;
+                if a == 0 {
+                    return None;
+                }
+                [r, g, b]
+            }
+            ColorSampling::AreaAverage => self.image.average_over_triangle(triangle)?,
+        };
+        let color = match &self.palette {
+            Some(palette) => quantize(color, palette),
+            None => color,
+        };
+        Some((
+            format_rgb(color),
+            format_rgb(shade(color, self.border_shade)),
+        ))
+    }
+}
+
+/// How a triangle is split into smaller triangles when it subdivides
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SubdivisionPattern {
+    /// Split into 4 triangles by connecting the midpoints of the edges
+    Midpoint,
+    /// Split into 3 triangles by connecting each vertex to the centroid
+    Centroid,
+}
+
+/// Settings for triangles subdividing in place over time
+#[derive(Clone)]
+pub struct SubdivisionConf {
+    pub pattern: SubdivisionPattern,
+    /// A triangle is subdivided every `interval` generations
+    pub interval: usize,
+    /// Triangles whose children would be smaller than this aren't subdivided
+    pub min_triangle_size: f32,
+}
+
+/// Settings for the gap-filling pass that runs when no full-sized triangle can be placed.  Regions
+/// where placement failed are retried with progressively smaller triangles.
+#[derive(Clone)]
+pub struct GapFillConf {
+    /// Each retry uses triangles this many times the size of the previous one
+    pub shrink_factor: f32,
+    /// Triangles are never shrunk below this size
+    pub min_triangle_size: f32,
+}
+
+/// Determines how a chain picks the triangles it branches from and the direction it grows in
+#[derive(Clone)]
+pub enum GrowthMode {
+    /// Branch from randomly selected triangles, rotating randomly around `rotation_offset`
+    RandomWalk,
+    /// Space colonization: branch from the triangles closest to the chain's attractor points and
+    /// grow toward them, consuming attractors as they're reached.  Falls back to a random walk
+    /// once no attractors are in range.
+    SpaceColonization {
+        /// Attractors only influence triangles whose centroids are within this distance
+        influence_radius: f32,
+        /// Attractors are consumed once a triangle is placed with its centroid this close
+        kill_radius: f32,
+        /// How strongly new triangles are rotated toward their attractors, from 0 to 1
+        steering_strength: f32,
+    },
+    /// Grow toward the chain's target point, which can be moved at any time to make the chain
+    /// follow it, for example to chase the pointer.  Branches from whichever of a few randomly
+    /// sampled triangles is closest to the target and keeps steering every new triangle toward
+    /// it, while pruning the oldest triangles retracts the chain's tail behind it.  Falls back to a
+    /// random walk while the chain has no target.
+    SeekTarget {
+        /// Number of random triangles sampled when picking a branch point, the closest of which to
+        /// the target is used.  Higher values follow the target more tightly.
+        branch_candidates: usize,
+        /// How strongly new triangles are rotated toward the target, from 0 to 1
+        steering_strength: f32,
+    },
+    /// Diffusion-limited aggregation: candidate triangles random-walk in from the canvas edges and
+    /// stick to the first triangle of the chain they touch, which becomes their parent.
+    DiffusionLimitedAggregation {
+        /// Distance moved by the walker in each step while it is near the chain
+        step_size: f32,
+        /// Walkers stick once they come within this distance of one of the chain's triangles
+        stick_distance: f32,
+        /// Walkers that haven't stuck after this many steps are discarded
+        max_walk_steps: usize,
+    },
+    /// Grows a Penrose rhombus tiling out of Robinson triangles with legs of length
+    /// `triangle_size`.  Each new triangle shares an edge with its parent and must satisfy the
+    /// matching rules along every edge it shares with the chain.  Fractal growth is ignored since
+    /// the tiles all need to be the same size.
+    Penrose,
+}
+
+/// Settings for depth-scaled growth where each child triangle is a scaled copy of its parent,
+/// producing self-similar fern/coral-like structures.
+#[derive(Clone)]
+pub struct FractalGrowthConf {
+    /// Each child's size is its parent's size multiplied by this factor
+    pub scale_factor: f32,
+    /// Maximum fraction by which `scale_factor` is randomly perturbed for each child
+    pub scale_jitter: f32,
+    /// Branches stop growing once their children would be smaller than this
+    pub min_triangle_size: f32,
+}
+
+/// Settings for a noise-based flow field that biases the rotation of new triangles toward the
+/// local field direction, causing chains to swirl coherently across the canvas.
+#[derive(Clone)]
+pub struct FlowFieldConf {
+    pub noise: PerlinNoise,
+    /// Noise frequency; smaller values produce larger, smoother swirls
+    pub scale: f32,
+    /// How far each triangle's rotation is pulled toward the field direction, from 0 to 1
+    pub strength: f32,
+    /// How far the field moves through time with each generated triangle
+    pub time_scale: f32,
+}
+
+impl FlowFieldConf {
+    /// Returns the heading of the flow field, in radians, at the given point and generation.
+    pub fn get_heading(&self, pt: Point2<f32>, generation: usize) -> f32 {
+        let val = self.noise.sample(
+            pt.x * self.scale,
+            pt.y * self.scale,
+            generation as f32 * self.time_scale,
+        );
+        // Perlin noise rarely strays far from 0, so a full turn either way covers every direction
+        // without making the headings wrap around more than once
+        val * f32::consts::TAU
+    }
+}
+
+/// Symmetry applied to every triangle placed in the field.  Each placed triangle is replicated
+/// into all of its symmetric copies, which collide and get pruned along with it.
+#[derive(Clone)]
+pub enum Symmetry {
+    None,
+    /// Mirrors triangles left-to-right across the vertical center line of the canvas
+    MirrorHorizontal,
+    /// Mirrors triangles top-to-bottom across the horizontal center line of the canvas
+    MirrorVertical,
+    /// `fold`-fold rotational symmetry around `center`
+    Radial {
+        fold: usize,
+        center: Point2<f32>,
+    },
+}
+
+impl Symmetry {
+    /// Returns all symmetric copies of `triangle`, not including the triangle itself
+    pub fn get_copies(&self, triangle: &TriangleBuf, conf: &Conf) -> Vec<TriangleBuf> {
+        match *self {
+            Symmetry::None => Vec::new(),
+            Symmetry::MirrorHorizontal => {
+                let width = conf.canvas_width as f32;
+                vec![triangle.map(|pt| p2(width - pt.x, pt.y))]
+            }
+            Symmetry::MirrorVertical => {
+                let height = conf.canvas_height as f32;
+                vec![triangle.map(|pt| p2(pt.x, height - pt.y))]
+            }
+            Symmetry::Radial { fold, center } => (1..fold)
+                .map(|i| {
+                    let isometry = Isometry2::rotation(f32::consts::TAU * i as f32 / fold as f32);
+                    triangle.map(|pt| center + isometry * (pt - center))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Settings for a grayscale image mapped onto the canvas that controls where triangles are placed.
+/// Placements are randomly rejected based on the brightness of the image under them, so chains
+/// cluster densely in dark areas and thin out in light ones.
+#[derive(Clone)]
+pub struct DensityMapConf {
+    pub image: CanvasImage,
+    /// Probability of accepting a placement over a white area of the image; placements over black
+    /// areas are always accepted
+    pub min_density: f32,
+    /// If set, triangles are scaled by `(dark_scale, light_scale)` interpolated by the brightness of
+    /// the image at their origin.  This compounds with fractal growth.
+    pub size_scale: Option<(f32, f32)>,
+}
+
+impl DensityMapConf {
+    /// Returns the probability that a triangle placed at `pt` is accepted
+    pub fn get_density(&self, pt: Point2<f32>) -> f32 {
+        let brightness = self.image.get_brightness(pt);
+        self.min_density + (1. - self.min_density) * (1. - brightness)
+    }
+
+    pub fn get_size_scale(&self, pt: Point2<f32>) -> f32 {
+        match self.size_scale {
+            Some((dark_scale, light_scale)) => {
+                dark_scale + (light_scale - dark_scale) * self.image.get_brightness(pt)
+            }
+            None => 1.,
+        }
+    }
+}
+
+/// Keeps triangles away from the pointer so that it parts the chains as it moves across the field
+#[derive(Clone, Copy, Debug)]
+pub struct PointerRepulsionConf {
+    pub radius: f32,
+    /// Seconds of movement at the pointer's current velocity that the repelled area extends ahead
+    /// of it, so that triangles are cleared out of its way before it reaches them
+    pub lead_time: f32,
+    /// `(position, velocity)` of the pointer, or `None` while it isn't over the field
+    pub pointer: Option<(Point2<f32>, Vector2<f32>)>,
+}
+
+impl PointerRepulsionConf {
+    /// Returns `true` if any part of `triangle` is within `radius` of the path that the pointer
+    /// will move along over the next `lead_time` seconds
+    pub fn intersects_triangle(&self, triangle: &TriangleBuf) -> bool {
+        let (position, velocity) = match self.pointer {
+            Some(pointer) => pointer,
+            None => return false,
+        };
+        distance_segment_to_triangle(position, position + velocity * self.lead_time, triangle)
+            <= self.radius
+    }
+}
+
+/// Determines what happens to existing triangles when the canvas is resized with `resize`
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResizePolicy {
+    /// Triangles stay where they are and any that no longer fit on the canvas are removed
+    Prune,
+    /// Triangles are stretched along with the canvas so that the field keeps its layout
+    Scale,
+    /// Triangles keep their distance from the top right corner of the canvas and any that no
+    /// longer fit are removed
+    AnchorTopRight,
+    /// Triangles keep their distance from the bottom left corner of the canvas and any that no
+    /// longer fit are removed
+    AnchorBottomLeft,
+    /// Triangles keep their distance from the bottom right corner of the canvas and any that no
+    /// longer fit are removed
+    AnchorBottomRight,
+    /// Triangles keep their distance from the center of the canvas and any that no longer fit are
+    /// removed
+    AnchorCenter,
+}
+
+impl ResizePolicy {
+    /// Returns the point that a point at `pt` on a canvas of size `old_size` moves to when the
+    /// canvas is resized to `new_size`
+    pub fn transform_point(
+        self,
+        pt: Point2<f32>,
+        old_size: Vector2<f32>,
+        new_size: Vector2<f32>,
+    ) -> Point2<f32> {
+        let growth = new_size - old_size;
+        match self {
+            ResizePolicy::Prune => pt,
+            ResizePolicy::Scale => p2(
+                pt.x * new_size.x / old_size.x,
+                pt.y * new_size.y / old_size.y,
+            ),
+            ResizePolicy::AnchorTopRight => pt + Vector2::new(growth.x, 0.),
+            ResizePolicy::AnchorBottomLeft => pt + Vector2::new(0., growth.y),
+            ResizePolicy::AnchorBottomRight => pt + growth,
+            ResizePolicy::AnchorCenter => pt + growth / 2.,
+        }
+    }
+}
+
+/// Determines what happens to a chain added with `spawn_chain` once its lifetime ends
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChainExpiry {
+    /// The chain's triangles are all removed at once
+    Remove,
+    /// The chain's triangles are handed over to the permanent chain closest to its seed, which
+    /// prunes them oldest first along with its own as it keeps growing.  The permanent chain can
+    /// hold more than its `triangle_count` triangles until then, and doesn't replace the triangles
+    /// it prunes until it is back down to that size.
+    Merge,
+}
+
+/// Maps the world units that the engine works in to the pixels that triangles are rendered in
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayScale {
+    /// CSS pixels per world unit, applied to triangles as they're rendered
+    pub pixels_per_unit: f32,
+}
+
+impl DisplayScale {
+    /// Converts a size in CSS pixels to whole world units
+    pub fn to_world_size(&self, pixels: usize) -> usize {
+        ((pixels as f32 / self.pixels_per_unit).round() as usize).max(1)
+    }
+
+    pub fn to_pixel_size(&self, units: usize) -> usize {
+        (units as f32 * self.pixels_per_unit).round() as usize
+    }
+}
+
+/// Settings that apply to the whole field rather than to individual chains
+struct FieldConf {
+    pub symmetry: Symmetry,
+    /// If set, triangles must lie entirely inside of this shape instead of the canvas rectangle.
+    /// It is ignored while wrapping is enabled.
+    pub canvas_shape: Option<Mask>,
+    pub display_scale: DisplayScale,
+    /// Rectangles that no triangle may overlap, such as the bounding boxes of page content.  Indexed
+    /// by the ids returned from `add_keep_out_rect`, with removed rectangles leaving `None` behind
+    /// so that ids are never reused.
+    pub keep_out_rects: Vec<Option<AABB<f32>>>,
+    /// If set, triangles must lie entirely inside of this region as well as the canvas
+    pub allowed_region: Option<Mask>,
+    /// Regions that no triangle may overlap, indexed by the ids returned from `add_forbidden_path`.
+    /// Removed regions leave `None` behind so that ids are never reused.
+    pub forbidden_regions: Vec<Option<Mask>>,
+    pub pointer_repulsion: Option<PointerRepulsionConf>,
+    pub density_map: Option<DensityMapConf>,
+    /// If set, the field wraps around at the edges of the canvas like a torus.  Triangles crossing
+    /// an edge get copies on the opposite side that render and collide like the original.
+    pub wrap: bool,
+    /// Most chains that can be spawned with `spawn_chain` at once
+    pub max_spawned_chains: usize,
+    /// Most triangles that all spawned chains combined can grow to
+    pub max_spawned_triangles: usize,
+    /// Stamp that the next triangle placed is given
+    pub next_triangle_stamp: usize,
+}
+
+impl FieldConf {
+    /// Returns `true` if `triangle` overlaps any of the keep-out rectangles or forbidden regions or
+    /// the area around the pointer
+    pub fn check_keep_out_collision(&self, triangle: &TriangleBuf) -> bool {
+        self.keep_out_rects
+            .iter()
+            .flatten()
+            .any(|rect| check_rect_collision(triangle, rect))
+            || self
+                .forbidden_regions
+                .iter()
+                .flatten()
+                .any(|region| region.intersects_triangle(triangle))
+            || self
+                .pointer_repulsion
+                .is_some_and(|repulsion| repulsion.intersects_triangle(triangle))
+    }
+
+    /// Returns all symmetric copies of `triangle` along with copies of it and its symmetric copies
+    /// wrapped around to the opposite edges of the canvas if wrapping is enabled
+    pub fn get_copies(&self, triangle: &TriangleBuf, conf: &Conf) -> Vec<TriangleBuf> {
+        let mut copies = self.symmetry.get_copies(triangle, conf);
+        if !self.wrap {
+            return copies;
+        }
+
+        let (width, height) = (conf.canvas_width as f32, conf.canvas_height as f32);
+        let canvas = AABB::new(p2(0., 0.), p2(width, height));
+        let symmetric_copy_count = copies.len();
+        for i in 0..=symmetric_copy_count {
+            let original = if i == 0 { *triangle } else { copies[i - 1] };
+            for &dx in &[-width, 0., width] {
+                for &dy in &[-height, 0., height] {
+                    if dx == 0. && dy == 0. {
+                        continue;
+                    }
+                    let offset = Vector2::new(dx, dy);
+                    let wrapped = original.map(|pt| pt + offset);
+                    if get_triangle_bv(&wrapped).intersects(&canvas) {
+                        copies.push(wrapped);
+                    }
+                }
+            }
+        }
+        copies
+    }
+
+    /// If wrapping is enabled, moves `triangle` by whole canvas sizes so that its centroid lies on
+    /// the canvas.  Parts of it that extend past the edges are covered by its wrapped copies.
+    pub fn wrap_triangle(&self, triangle: TriangleBuf, conf: &Conf) -> TriangleBuf {
+        if !self.wrap {
+            return triangle;
+        }
+
+        let (width, height) = (conf.canvas_width as f32, conf.canvas_height as f32);
+        let center = centroid(&triangle);
+        let offset = Vector2::new(
+            center.x.rem_euclid(width) - center.x,
+            center.y.rem_euclid(height) - center.y,
+        );
+        triangle.map(|pt| pt + offset)
+    }
+
+    /// Returns a point picked uniformly at random from the part of `bounds` inside of the canvas
+    /// shape, or from all of `bounds` if there is no canvas shape.  Gives up and returns the center
+    /// of `bounds` if no point inside of the shape is found.
+    pub fn get_random_canvas_point(&self, bounds: &AABB<f32>) -> Point2<f32> {
+        let shape = self.canvas_shape.as_ref().filter(|_| !self.wrap);
+        get_random_point_in(bounds, shape).unwrap_or_else(|| bounds.center())
+    }
+
+    /// Returns `true` if `triangle` lies inside of the canvas and the allowed region without
+    /// overlapping anything that triangles are kept out of.  Triangles may cross the edges of the
+    /// canvas if wrapping is enabled.
+    pub fn is_triangle_allowed(&self, triangle: &TriangleBuf, conf: &Conf) -> bool {
+        let pt_within_canvas = |pt: &Point2<f32>| {
+            pt.x > 0.
+                && pt.x < conf.canvas_width as f32
+                && pt.y > 0.
+                && pt.y < conf.canvas_height as f32
+        };
+        let within_canvas = self.wrap
+            || match &self.canvas_shape {
+                Some(shape) => shape.contains_triangle(triangle),
+                None => triangle.iter().all(pt_within_canvas),
+            };
+        within_canvas
+            && self
+                .allowed_region
+                .as_ref()
+                .is_none_or(|region| region.contains_triangle(triangle))
+            && !self.check_keep_out_collision(triangle)
+    }
+
+    /// Randomly decides whether to accept a placement of `triangle` based on the density map
+    pub fn roll_density(&self, triangle: &TriangleBuf) -> bool {
+        match &self.density_map {
+            Some(density_map) => {
+                rng().gen_range(0., 1.) < density_map.get_density(centroid(triangle))
+            }
+            None => true,
+        }
+    }
+
+    /// Returns the factor by which triangles placed at `pt` are scaled by the density map
+    pub fn get_size_scale(&self, pt: Point2<f32>) -> f32 {
+        self.density_map
+            .as_ref()
+            .map_or(1., |density_map| density_map.get_size_scale(pt))
+    }
 }
 
 impl Conf {
+    /// Returns `true` if `triangle` lies inside of the chain's territory and leash, if it has them
+    fn is_in_territory(&self, triangle: &TriangleBuf) -> bool {
+        self.territory
+            .as_ref()
+            .is_none_or(|territory| territory.contains_triangle(triangle))
+            && self
+                .leash
+                .is_none_or(|leash| leash.contains_triangle(triangle))
+    }
+
     /// Returns `(offset_x, offset_y)`
     fn get_base_triangle_offsets(&self) -> (f32, f32) {
         let triangle_offset_x = self.triangle_size / 2.0;
@@ -62,47 +670,166 @@ impl Conf {
     }
 }
 
+/// Builds a polygon from a flat list of `[x1, y1, x2, y2, ...]` pairs, returning `None` if it has
+/// fewer than 3 vertices
+fn build_polygon_mask(coords: &[f32]) -> Option<Mask> {
+    let points: Vec<Point2<f32>> = coords
+        .chunks_exact(2)
+        .map(|coords| p2(coords[0], coords[1]))
+        .collect();
+    if points.len() < 3 {
+        return None;
+    }
+    Some(Mask::polygon(points))
+}
+
 fn get_triangle_bv(triangle: &TriangleBuf) -> AABB<f32> {
     let (min, max) = bounds(triangle[0], triangle[1], triangle[2]);
     AABB::new(min, max)
 }
 
+/// Picks a point uniformly at random from the part of `bounds` inside of `mask`, or from all of
+/// `bounds` if there is no mask.  Returns `None` if no point inside of the mask is found after
+/// `MAX_MASK_SAMPLES` tries.
+fn get_random_point_in(bounds: &AABB<f32>, mask: Option<&Mask>) -> Option<Point2<f32>> {
+    let (mins, maxs) = (bounds.mins, bounds.maxs);
+    (0..MAX_MASK_SAMPLES)
+        .map(|_| {
+            p2(
+                rng().gen_range(mins.x, maxs.x.max(mins.x + 1.)),
+                rng().gen_range(mins.y, maxs.y.max(mins.y + 1.)),
+            )
+        })
+        .find(|pt| mask.is_none_or(|mask| mask.contains_point(*pt)))
+}
+
+/// Returns `true` if `triangle` can be placed as the first triangle of a chain
+fn is_valid_seed(conf: &Conf, triangle: &TriangleBuf) -> bool {
+    !check_world_collision(triangle, &get_triangle_bv(triangle))
+        && field_conf().is_triangle_allowed(triangle, conf)
+        && conf.is_in_territory(triangle)
+        && field_conf().roll_density(triangle)
+}
+
+/// Returns the first triangle for a chain along with the rotation to continue growing from,
+/// following the chain's spawn settings if it has any.  Returns `None` if there is no free spot to
+/// seed the chain at.
 fn get_initial_triangle(
-    chain_ix: usize,
     conf: &Conf,
     base_triangle_coords: &TriangleBuf,
-) -> (TriangleBuf, f32) {
-    let initial_offset = Vector2::new(
-        rng().gen_range(
-            conf.triangle_size,
-            conf.canvas_width as f32 - conf.triangle_size,
-        ),
-        rng().gen_range(
-            conf.triangle_size,
-            conf.canvas_height as f32 - conf.triangle_size,
-        ),
-    );
-    let rotation = rng().gen_range(0.0, f32::consts::PI / 2.0);
-    let proposed_first_triangle = [
-        base_triangle_coords[0] + initial_offset,
-        base_triangle_coords[1] + initial_offset,
-        base_triangle_coords[2] + initial_offset,
-    ];
-    // verify that this proposed initial triangle doesn't intersect any existing triangles
-    let bounding_box = get_triangle_bv(&proposed_first_triangle);
-    let mut does_collide = false;
-    let mut visitor = TriangleCollisionVisitor {
-        triangle: &proposed_first_triangle,
-        triangle_bv: &bounding_box,
-        // triangles: unsafe { &*TRIANGLES },
-        does_collide: &mut does_collide,
+) -> Option<(TriangleBuf, f32)> {
+    conf.spawn
+        .as_ref()
+        .and_then(|spawn| get_spawn_triangle(conf, base_triangle_coords, spawn))
+        .or_else(|| get_random_initial_triangle(conf, base_triangle_coords))
+}
+
+fn get_spawn_triangle(
+    conf: &Conf,
+    base_triangle_coords: &TriangleBuf,
+    spawn: &Spawn,
+) -> Option<(TriangleBuf, f32)> {
+    let build_seed = |tip: Point2<f32>, rotation: f32| -> TriangleBuf {
+        let isometry = Isometry2::new(tip.coords, rotation);
+        base_triangle_coords.map(|pt| isometry * pt)
     };
-    world().visit(&mut visitor);
-    if does_collide {
-        return get_initial_triangle(chain_ix, conf, base_triangle_coords);
+    let search_around = |position: Point2<f32>, rotation: f32| {
+        search_for_seed(
+            conf,
+            base_triangle_coords,
+            position,
+            rotation,
+            SPAWN_SEARCH_ATTEMPTS,
+        )
+    };
+
+    match spawn {
+        Spawn::Point { position, rotation } => search_around(*position, *rotation),
+        Spawn::Region(region) => {
+            for _ in 0..SPAWN_REGION_ATTEMPTS {
+                let tip = get_random_point_in(&region.bounds, Some(region))?;
+                let rotation = rng().gen_range(0., f32::consts::TAU);
+                let triangle = build_seed(tip, rotation);
+                if is_valid_seed(conf, &triangle) {
+                    return Some((triangle, rotation));
+                }
+            }
+            // the region may be covered up, for example by a keep-out rectangle over the same
+            // area, so start from next to it instead
+            let rotation = rng().gen_range(0., f32::consts::TAU);
+            search_around(region.bounds.center(), rotation)
+        }
     }
+}
+
+/// Tries seed triangles with their tips at random angles around `position`, moving further out with
+/// each of the `attempts`, and returns the first valid one along with `rotation`.
+fn search_for_seed(
+    conf: &Conf,
+    base_triangle_coords: &TriangleBuf,
+    position: Point2<f32>,
+    rotation: f32,
+    attempts: usize,
+) -> Option<(TriangleBuf, f32)> {
+    let isometry = |tip: Point2<f32>| Isometry2::new(tip.coords, rotation);
+    (0..attempts).find_map(|attempt| {
+        let distance = attempt as f32 * conf.triangle_size * SPAWN_SEARCH_STEP;
+        let angle = rng().gen_range(0., f32::consts::TAU);
+        let tip = position + Vector2::new(angle.cos(), angle.sin()) * distance;
+        let triangle = base_triangle_coords.map(|pt| isometry(tip) * pt);
+        Some((triangle, rotation)).filter(|_| is_valid_seed(conf, &triangle))
+    })
+}
 
-    (proposed_first_triangle, rotation)
+/// Picks a random spot on the canvas for the first triangle of a chain.  Returns `None` if none of
+/// the `RANDOM_SEED_ATTEMPTS` spots tried are free, for example because the canvas is covered by
+/// keep-out rectangles.
+fn get_random_initial_triangle(
+    conf: &Conf,
+    base_triangle_coords: &TriangleBuf,
+) -> Option<(TriangleBuf, f32)> {
+    let (mut min_x, mut max_x) = (
+        conf.triangle_size,
+        conf.canvas_width as f32 - conf.triangle_size,
+    );
+    let (mut min_y, mut max_y) = (
+        conf.triangle_size,
+        conf.canvas_height as f32 - conf.triangle_size,
+    );
+    // only sample the parts of the canvas covered by the canvas shape, allowed region, and the
+    // chain's territory if they're big enough to fit a triangle
+    let canvas_shape = field_conf()
+        .canvas_shape
+        .as_ref()
+        .filter(|_| !field_conf().wrap);
+    let region_bounds = canvas_shape
+        .into_iter()
+        .chain(&field_conf().allowed_region)
+        .chain(conf.territory.as_deref())
+        .map(|region| region.bounds)
+        .chain(conf.leash.map(|leash| leash.get_bounds()));
+    for bounds in region_bounds {
+        let (mins, maxs) = (bounds.mins, bounds.maxs);
+        if maxs.x - mins.x > conf.triangle_size * 2. && maxs.y - mins.y > conf.triangle_size * 2. {
+            min_x = min_x.max(mins.x + conf.triangle_size);
+            max_x = max_x.min(maxs.x - conf.triangle_size);
+            min_y = min_y.max(mins.y + conf.triangle_size);
+            max_y = max_y.min(maxs.y - conf.triangle_size);
+        }
+    }
+    let bounds = AABB::new(p2(min_x, min_y), p2(max_x, max_y));
+    (0..RANDOM_SEED_ATTEMPTS).find_map(|_| {
+        let initial_offset = field_conf().get_random_canvas_point(&bounds).coords;
+        let rotation = rng().gen_range(0.0, f32::consts::PI / 2.0);
+        let proposed_first_triangle = [
+            base_triangle_coords[0] + initial_offset,
+            base_triangle_coords[1] + initial_offset,
+            base_triangle_coords[2] + initial_offset,
+        ];
+        // verify that this proposed initial triangle doesn't intersect any existing triangles
+        Some((proposed_first_triangle, rotation))
+            .filter(|_| is_valid_seed(conf, &proposed_first_triangle))
+    })
 }
 
 struct Env {
@@ -114,13 +841,50 @@ struct Env {
     pub last_triangle: TriangleBuf,
     pub last_triangle_ix: usize,
     pub rotation: f32,
+    /// Tip of the seed triangle that the chain most recently started growing from
+    pub seed_point: Point2<f32>,
     pub oldest_triangle_ix: usize,
+    /// Number of times `generate` has been called for this chain
+    pub generation: usize,
+    /// Points that the chain grows toward when using `GrowthMode::SpaceColonization`
+    pub attractors: Vec<Point2<f32>>,
+    /// Point that the chain grows toward when using `GrowthMode::SeekTarget`
+    pub target: Option<Point2<f32>>,
+    /// `(heading, strength)` that the next triangle's growth direction should be pulled toward
+    pub steering: Option<(f32, f32)>,
+    /// Origins at which placing a triangle failed during the current call to `place_triangle`
+    pub failed_origins: Vec<Point2<f32>>,
+    /// Set for chains added with `spawn_chain`, which end after being generated a set number of
+    /// times
+    pub lifetime: Option<ChainLifetime>,
+    /// Unset once a spawned chain has been removed, leaving its slot free for the next one
+    pub active: bool,
+    /// Set while there is no free spot to seed the chain at, during which it doesn't grow.  Each
+    /// call to `generate` tries to seed it again.
+    pub dormant: bool,
+}
+
+/// How long a spawned chain lives and what happens to it afterward
+#[derive(Clone, Copy, Debug)]
+struct ChainLifetime {
+    /// Number of calls to `generate` left before the chain ends
+    pub remaining: usize,
+    pub expiry: ChainExpiry,
 }
 
 impl Env {
     pub fn new(conf: Conf, chain_ix: usize) -> Self {
         // Re-seed the global PRNG
-        *rng() = Pcg32::from_seed(unsafe { mem::transmute((conf.prng_seed, conf.prng_seed)) });
+        *rng() = Pcg32::from_seed(unsafe {
+            mem::transmute::<(f64, f64), [u8; 16]>((conf.prng_seed, conf.prng_seed))
+        });
+        let mut env = Env::new_unseeded(conf, chain_ix);
+        env.reseed();
+        env
+    }
+
+    /// Creates a chain whose seed triangle hasn't been picked yet
+    fn new_unseeded(conf: Conf, chain_ix: usize) -> Self {
         let (triangle_offset_x, triangle_offset_y) = conf.get_base_triangle_offsets();
 
         let base_triangle_coords = [
@@ -128,8 +892,6 @@ impl Env {
             p2(-triangle_offset_x, triangle_offset_y),
             p2(triangle_offset_x, triangle_offset_y),
         ];
-        let (last_triangle, rotation) =
-            get_initial_triangle(chain_ix, &conf, &base_triangle_coords);
 
         Env {
             chain_ix,
@@ -138,99 +900,456 @@ impl Env {
             // triangle_offset_y,
             base_triangle_coords,
             last_triangle_ix: usize::MAX,
-            last_triangle,
-            rotation,
+            last_triangle: base_triangle_coords,
+            rotation: 0.,
+            seed_point: Point2::origin(),
             oldest_triangle_ix: usize::MAX,
+            generation: 0,
+            attractors: Vec::new(),
+            target: None,
+            steering: None,
+            failed_origins: Vec::new(),
+            lifetime: None,
+            active: true,
+            dormant: false,
+        }
+    }
+
+    /// Forgets the chain's triangles, which must already have been removed, and picks a new random
+    /// spot to seed it from
+    pub fn reset(&mut self) {
+        self.last_triangle_ix = usize::MAX;
+        self.oldest_triangle_ix = usize::MAX;
+        self.failed_origins.clear();
+        self.set_random_last_triangle();
+    }
+
+    /// Changes the size of newly placed triangles to `triangle_size`
+    pub fn set_triangle_size(&mut self, triangle_size: f32) {
+        self.conf.triangle_size = triangle_size;
+        let (triangle_offset_x, triangle_offset_y) = self.conf.get_base_triangle_offsets();
+        self.base_triangle_coords = [
+            Point2::origin(),
+            p2(-triangle_offset_x, triangle_offset_y),
+            p2(triangle_offset_x, triangle_offset_y),
+        ];
+    }
+
+    /// Picks a new seed triangle for the chain to start growing from, following its spawn settings.
+    /// If there is no free spot for one the chain becomes dormant until a later attempt succeeds.
+    pub fn reseed(&mut self) {
+        match get_initial_triangle(&self.conf, &self.base_triangle_coords) {
+            Some((triangle, rotation)) => self.set_seed(triangle, rotation),
+            None => {
+                self.last_triangle_ix = usize::MAX;
+                self.dormant = true;
+            }
         }
     }
 
+    /// Makes the chain start growing from the seed triangle `triangle` at `rotation`
+    fn set_seed(&mut self, triangle: TriangleBuf, rotation: f32) {
+        self.dormant = false;
+        self.rotation = rotation;
+        self.last_triangle = triangle;
+        self.last_triangle_ix = usize::MAX;
+        self.seed_point = triangle[0];
+    }
+
     pub fn set_new_last_triangle(&mut self) {
+        self.steering = None;
+        if let GrowthMode::SpaceColonization {
+            influence_radius,
+            steering_strength,
+            ..
+        } = self.conf.growth_mode
+        {
+            if self.select_colonization_branch(influence_radius, steering_strength) {
+                return;
+            }
+        }
+        if let GrowthMode::SeekTarget {
+            branch_candidates,
+            steering_strength,
+        } = self.conf.growth_mode
+        {
+            if self.select_seeking_branch(branch_candidates, steering_strength) {
+                return;
+            }
+        }
+
+        self.set_random_last_triangle();
+    }
+
+    /// Picks a random triangle of the chain to branch from, re-seeding the chain if there are none.
+    pub fn set_random_last_triangle(&mut self) {
+        self.steering = None;
         let triangle_count = triangles(self.chain_ix).len();
-        if triangle_count == 0 {
-            let (triangle, rotation) =
-                get_initial_triangle(self.chain_ix, &self.conf, &self.base_triangle_coords);
-            self.rotation = rotation;
-            self.last_triangle = triangle;
-            self.last_triangle_ix = usize::MAX;
-            return;
+        let has_branchable_triangle = self
+            .triangles()
+            .iter()
+            .enumerate()
+            .any(|(ix, handle)| !handle.removed && ix != self.oldest_triangle_ix);
+        if !has_branchable_triangle {
+            return self.reseed();
         }
 
         let ix = rng().gen_range(0, triangle_count);
-        if ix == self.oldest_triangle_ix {
-            return self.set_new_last_triangle();
+        if ix == self.oldest_triangle_ix || self.get_triangle(ix).removed {
+            return self.set_random_last_triangle();
         }
         self.last_triangle = triangles(self.chain_ix)[ix].geometry;
         self.last_triangle_ix = ix;
     }
 
-    #[inline(always)]
-    pub fn triangles(&self) -> &'static Vec<TriangleHandle> {
-        triangles(self.chain_ix)
-    }
+    /// Finds the triangles that are the closest ones to at least one attractor within
+    /// `influence_radius` and picks one of them at random to branch from, steering toward the
+    /// average direction of the attractors it is closest to.  Returns `false` if no attractors are
+    /// in range of any triangle that can accept a new child.
+    fn select_colonization_branch(
+        &mut self,
+        influence_radius: f32,
+        steering_strength: f32,
+    ) -> bool {
+        // `(triangle_ix, sum of unit vectors pointing toward the triangle's attractors)`
+        let mut influenced_triangles: Vec<(usize, Vector2<f32>)> = Vec::new();
+        let reach = Vector2::new(influence_radius, influence_radius);
+        for attractor in &self.attractors {
+            // only the triangles whose bounding volumes come within `influence_radius` of the
+            // attractor can have their centroids within it
+            let nearby =
+                query_triangles(&AABB::new(attractor - reach, attractor + reach), |_| true);
+            let mut closest: Option<(usize, Vector2<f32>, f32)> = None;
+            for (chain_ix, ix, copy_ix) in nearby {
+                if chain_ix != self.chain_ix || copy_ix != 0 || ix == self.oldest_triangle_ix {
+                    continue;
+                }
+                let handle = self.get_triangle(ix);
+                if !handle.has_free_child_slot() {
+                    continue;
+                }
 
-    #[inline(always)]
-    pub fn triangles_mut(&self) -> &'static mut Vec<TriangleHandle> {
-        triangles_mut(self.chain_ix)
-    }
+                let offset = attractor - centroid(&handle.geometry);
+                let distance = offset.norm();
+                if distance < influence_radius
+                    && closest.is_none_or(|(_, _, closest_distance)| distance < closest_distance)
+                {
+                    closest = Some((ix, offset, distance));
+                }
+            }
 
-    #[inline(always)]
-    pub fn get_triangle(&self, i: usize) -> &'static TriangleHandle {
-        get_triangle(self.chain_ix, i)
-    }
+            if let Some((ix, offset, distance)) = closest {
+                let direction = if distance > 0. {
+                    offset / distance
+                } else {
+                    Vector2::zeros()
+                };
+                match influenced_triangles.iter_mut().find(|(i, _)| *i == ix) {
+                    Some((_, sum)) => *sum += direction,
+                    None => influenced_triangles.push((ix, direction)),
+                }
+            }
+        }
 
-    #[inline(always)]
-    pub fn get_triangle_mut(&self, i: usize) -> &'static mut TriangleHandle {
-        get_triangle_mut(self.chain_ix, i)
+        if influenced_triangles.is_empty() {
+            return false;
+        }
+
+        let (ix, direction) = influenced_triangles[rng().gen_range(0, influenced_triangles.len())];
+        self.last_triangle = self.get_triangle(ix).geometry;
+        self.last_triangle_ix = ix;
+        self.steering = Some((direction.y.atan2(direction.x), steering_strength));
+        true
     }
-}
 
-#[derive(Debug)]
-struct TriangleHandle {
-    pub geometry: TriangleBuf,
-    pub collider_handle: DBVTLeafId,
-    pub dom_id: usize,
-    pub prev_node: Option<usize>,
-    pub next_node_1: Option<usize>,
-    pub next_node_2: Option<usize>,
-}
+    /// Samples up to `branch_candidates` random triangles that can accept a new child and branches
+    /// from the one closest to the chain's target, steering toward it.  Returns `false` if the
+    /// chain has no target or no sampled triangle can be branched from.
+    fn select_seeking_branch(&mut self, branch_candidates: usize, steering_strength: f32) -> bool {
+        let target = match self.target {
+            Some(target) => target,
+            None => return false,
+        };
+        let triangle_count = self.triangles().len();
+        if triangle_count == 0 {
+            return false;
+        }
 
-impl TriangleHandle {
-    pub fn degree(&self) -> usize {
-        let mut degree = 0;
-        for link in &[self.prev_node, self.next_node_1, self.next_node_2] {
-            if link.is_some() {
-                degree += 1;
+        let mut closest: Option<(usize, f32)> = None;
+        for _ in 0..branch_candidates.max(1) {
+            let ix = rng().gen_range(0, triangle_count);
+            let handle = self.get_triangle(ix);
+            if ix == self.oldest_triangle_ix || handle.removed || !handle.has_free_child_slot() {
+                continue;
+            }
+
+            let distance = (target - centroid(&handle.geometry)).norm();
+            if closest.is_none_or(|(_, closest_distance)| distance < closest_distance) {
+                closest = Some((ix, distance));
             }
         }
-        degree
-    }
-}
 
-#[inline(always)]
+        let ix = match closest {
+            Some((ix, _)) => ix,
+            None => return false,
+        };
+        self.last_triangle = self.get_triangle(ix).geometry;
+        self.last_triangle_ix = ix;
+        self.steer_toward_target(steering_strength);
+        true
+    }
+
+    /// Points `steering` from the last triangle toward the chain's target, clearing it if there is
+    /// no target or the last triangle is already on top of it.
+    fn steer_toward_target(&mut self, steering_strength: f32) {
+        self.steering = self.target.and_then(|target| {
+            let offset = target - centroid(&self.last_triangle);
+            if offset.norm() < f32::EPSILON {
+                return None;
+            }
+            Some((offset.y.atan2(offset.x), steering_strength))
+        });
+    }
+
+    /// Removes all attractors within `kill_radius` of the centroid of `triangle`.
+    fn consume_attractors(&mut self, triangle: &TriangleBuf, kill_radius: f32) {
+        let center = centroid(triangle);
+        self.attractors
+            .retain(|attractor| (attractor - center).norm() > kill_radius);
+    }
+
+    pub fn live_triangle_count(&self) -> usize {
+        self.triangles()
+            .iter()
+            .filter(|handle| !handle.removed)
+            .count()
+    }
+
+    /// Drops removed slots from the end of the chain.  The chain prunes its first slot next if the
+    /// one it was about to prune is dropped, and picks a new triangle to grow from if its last
+    /// triangle's slot is dropped.
+    fn trim_removed_slots(&mut self) {
+        while self.triangles().last().is_some_and(|handle| handle.removed) {
+            self.triangles_mut().pop();
+        }
+        let slot_count = self.triangles().len();
+        if self.oldest_triangle_ix != usize::MAX && self.oldest_triangle_ix >= slot_count {
+            self.oldest_triangle_ix = 0;
+        }
+        if self.last_triangle_ix != usize::MAX && self.last_triangle_ix >= slot_count {
+            self.set_new_last_triangle();
+        }
+    }
+
+    /// Returns the index of a slot that a new triangle can be stored in, re-using the slots of
+    /// removed triangles other than the one currently being replaced where possible.
+    pub fn get_free_slot_ix(&self) -> usize {
+        self.triangles()
+            .iter()
+            .enumerate()
+            .position(|(ix, handle)| handle.removed && ix != self.oldest_triangle_ix)
+            .unwrap_or_else(|| self.triangles().len())
+    }
+
+    /// Builds a triangle of size `size` with its tip at `origin`, rotated by `rotation`, and wrapped
+    /// back onto the canvas if it lies past the edge of a wrapping field
+    pub fn build_triangle(&self, origin: Point2<f32>, rotation: f32, size: f32) -> TriangleBuf {
+        let isometry = Isometry2::new(origin.coords, rotation);
+        let scale = size / self.conf.triangle_size;
+        field_conf().wrap_triangle(
+            self.base_triangle_coords.map(|pt| isometry * (pt * scale)),
+            &self.conf,
+        )
+    }
+
+    /// Returns the size of the next triangle to be attached to `last_triangle`, or `None` if the
+    /// branch has become too small to continue growing.
+    pub fn get_child_triangle_size(&self) -> Option<f32> {
+        if self.last_triangle_ix == usize::MAX {
+            return Some(self.conf.triangle_size);
+        }
+
+        let fractal_conf = match &self.conf.fractal_growth {
+            Some(fractal_conf) => fractal_conf,
+            None => return Some(self.conf.triangle_size),
+        };
+        let jitter = if fractal_conf.scale_jitter > 0. {
+            rng().gen_range(-fractal_conf.scale_jitter, fractal_conf.scale_jitter)
+        } else {
+            0.
+        };
+        let size = self.get_triangle(self.last_triangle_ix).size
+            * fractal_conf.scale_factor
+            * (1. + jitter);
+        if size < fractal_conf.min_triangle_size {
+            None
+        } else {
+            Some(size)
+        }
+    }
+
+    #[inline(always)]
+    pub fn triangles(&self) -> &'static Vec<TriangleHandle> {
+        triangles(self.chain_ix)
+    }
+
+    #[inline(always)]
+    pub fn triangles_mut(&self) -> &'static mut Vec<TriangleHandle> {
+        triangles_mut(self.chain_ix)
+    }
+
+    #[inline(always)]
+    pub fn get_triangle(&self, i: usize) -> &'static TriangleHandle {
+        get_triangle(self.chain_ix, i)
+    }
+
+    #[inline(always)]
+    pub fn get_triangle_mut(&self, i: usize) -> &'static mut TriangleHandle {
+        get_triangle_mut(self.chain_ix, i)
+    }
+}
+
+/// A symmetric or wrapped copy of a placed triangle which is rendered and collides independently of
+/// it
+#[derive(Debug)]
+struct TriangleCopy {
+    pub geometry: TriangleBuf,
+    pub collider_handle: DBVTLeafId,
+    pub dom_id: usize,
+    pub color: String,
+    pub border_color: String,
+}
+
+#[derive(Debug)]
+struct TriangleHandle {
+    pub geometry: TriangleBuf,
+    pub collider_handle: DBVTLeafId,
+    pub dom_id: usize,
+    /// Colors that the triangle was rendered with, kept for exporting the field
+    pub color: String,
+    pub border_color: String,
+    pub prev_node: Option<usize>,
+    pub next_node_1: Option<usize>,
+    pub next_node_2: Option<usize>,
+    /// Length of this triangle's sides, or their mean length for the uneven triangles left by
+    /// centroid subdivision
+    pub size: f32,
+    /// Number of ancestors between this triangle and the root of the chain it was grown from
+    pub depth: usize,
+    /// Value of the chain's `generation` when the triangle was placed
+    pub generation: usize,
+    /// Number that no other triangle placed on the field has, which tells apart the triangles that
+    /// have occupied the same slot
+    pub stamp: usize,
+    pub copies: Vec<TriangleCopy>,
+    /// Set once the triangle has been deleted without anything being placed into its slot yet
+    pub removed: bool,
+}
+
+impl TriangleHandle {
+    pub fn degree(&self) -> usize {
+        let mut degree = 0;
+        for link in &[self.prev_node, self.next_node_1, self.next_node_2] {
+            if link.is_some() {
+                degree += 1;
+            }
+        }
+        degree
+    }
+
+    pub fn has_free_child_slot(&self) -> bool {
+        self.next_node_1.is_none() || self.next_node_2.is_none()
+    }
+
+    /// Returns the geometry of the triangle itself if `copy_ix` is 0 or of its `copy_ix`th copy
+    pub fn get_geometry(&self, copy_ix: usize) -> &TriangleBuf {
+        if copy_ix == 0 {
+            &self.geometry
+        } else {
+            &self.copies[copy_ix - 1].geometry
+        }
+    }
+}
+
+#[inline(always)]
 fn p2(x: f32, y: f32) -> Point2<f32> {
     Point2::new(x, y)
 }
 
 #[inline]
 fn render_triangle_array(triangle: &TriangleBuf, color: &str, border_color: &str) -> usize {
-    let [p1, p2, p3] = *triangle;
+    let pixels_per_unit = field_conf().display_scale.pixels_per_unit;
+    let [p1, p2, p3] = triangle.map(|pt| pt * pixels_per_unit);
     render_triangle(p1.x, p1.y, p2.x, p2.y, p3.x, p3.y, color, border_color)
 }
 
-/// Clear out the collision world and empty the geometry buffer
+/// Removes a triangle and all of its copies from the DOM and the collision world
+fn remove_triangle_geometry(handle: &TriangleHandle) {
+    delete_elem(handle.dom_id);
+    world().remove(handle.collider_handle);
+    for copy in &handle.copies {
+        delete_elem(copy.dom_id);
+        world().remove(copy.collider_handle);
+    }
+}
+
+/// Removes the triangle at `triangle_ix` from the chain, unlinking it from its parent and
+/// children and marking its slot as removed.  Its children become the roots of their own branches.
+fn remove_triangle(env: &Env, triangle_ix: usize) {
+    remove_triangle_geometry(env.get_triangle(triangle_ix));
+    unlink_triangle(env, triangle_ix);
+}
+
+/// Unlinks the triangle at `triangle_ix` from its parent and children and marks its slot as
+/// removed without touching its rendered elements or colliders.
+fn unlink_triangle(env: &Env, triangle_ix: usize) {
+    let handle = env.get_triangle(triangle_ix);
+    if let Some(prev_ix) = handle.prev_node {
+        if env.get_triangle(prev_ix).next_node_1 == Some(triangle_ix) {
+            env.get_triangle_mut(prev_ix).next_node_1 = None;
+        } else if env.get_triangle(prev_ix).next_node_2 == Some(triangle_ix) {
+            env.get_triangle_mut(prev_ix).next_node_2 = None;
+        } else {
+            panic!("Tried to delete triangle but its parent doesn't list it as its child");
+        }
+    }
+    if let Some(child_ix) = handle.next_node_1 {
+        debug_assert!(env.get_triangle(child_ix).prev_node == Some(triangle_ix));
+        env.get_triangle_mut(child_ix).prev_node = None;
+    }
+    if let Some(child_ix) = handle.next_node_2 {
+        debug_assert!(env.get_triangle(child_ix).prev_node == Some(triangle_ix));
+        env.get_triangle_mut(child_ix).prev_node = None;
+    }
+    let handle = env.get_triangle_mut(triangle_ix);
+    handle.prev_node = None;
+    handle.next_node_1 = None;
+    handle.next_node_2 = None;
+    handle.removed = true;
+}
+
+/// Clear out the collision world and empty the geometry buffer, dropping any spawned chains
 fn reinitialize_global_state() {
+    clear_spawned_chains();
     let world = unsafe { &mut *COLLISION_WORLD };
     let triangles = unsafe { &mut *TRIANGLES };
     *world = DBVT::new();
-    triangles.iter_mut().for_each(|v| v.clear())
+    triangles.truncate(CHAIN_COUNT);
+    triangles.iter_mut().for_each(|v| v.clear());
+    unsafe { (&mut *ENVS).truncate(CHAIN_COUNT) };
 }
 
-/// DBVT with custom data as `(chain_ix, triangle_ix)`
-type World = DBVT<f32, (usize, usize), AABB<f32>>;
+/// DBVT with custom data as `(chain_ix, triangle_ix, copy_ix)`, where `copy_ix` is 0 for the
+/// triangle itself and `n` for its `n`th symmetric copy
+type World = DBVT<f32, (usize, usize, usize), AABB<f32>>;
 static mut COLLISION_WORLD: *mut World = ptr::null_mut();
-static mut TRIANGLES: *mut [Vec<TriangleHandle>; CHAIN_COUNT] = ptr::null_mut();
+static mut FIELD_CONF: *mut FieldConf = ptr::null_mut();
+/// Triangles of each chain, with the permanent chains first followed by any spawned chains
+static mut TRIANGLES: *mut Vec<Vec<TriangleHandle>> = ptr::null_mut();
 static mut RNG: *mut Pcg32 = ptr::null_mut();
-static mut ENVS: *mut [Env; CHAIN_COUNT] = ptr::null_mut();
+static mut ENVS: *mut Vec<Env> = ptr::null_mut();
+/// Set while the field is an infinite chunked world rather than confined to the canvas
+static mut CHUNKED_WORLD: *mut ChunkedWorld = ptr::null_mut();
 
 #[inline(always)]
 fn rng() -> &'static mut Pcg32 {
@@ -282,6 +1401,16 @@ fn world() -> &'static mut World {
     unsafe { &mut *COLLISION_WORLD }
 }
 
+#[inline(always)]
+fn chunked_world() -> Option<&'static mut ChunkedWorld> {
+    unsafe { CHUNKED_WORLD.as_mut() }
+}
+
+#[inline(always)]
+fn field_conf() -> &'static mut FieldConf {
+    unsafe { &mut *FIELD_CONF }
+}
+
 #[wasm_bindgen]
 pub fn init_triangles(canvas_width: usize, canvas_height: usize) {
     if cfg!(debug_assertions) {
@@ -291,26 +1420,35 @@ pub fn init_triangles(canvas_width: usize, canvas_height: usize) {
     let world: Box<World> = Box::new(DBVT::new());
     let p: *mut World = Box::into_raw(world);
     unsafe { COLLISION_WORLD = p };
+    unsafe { CHUNKED_WORLD = ptr::null_mut() };
 
-    let triangles: Box<[Vec<TriangleHandle>; CHAIN_COUNT]> =
-        unsafe { Box::new(mem::MaybeUninit::uninit().assume_init()) };
-    let p: *mut [Vec<TriangleHandle>; CHAIN_COUNT] = Box::into_raw(triangles);
-    for i in 0..CHAIN_COUNT {
-        unsafe {
-            ptr::write(
-                (p as *mut Vec<TriangleHandle>).add(i),
-                Vec::with_capacity(200),
-            )
-        };
-    }
+    let field_conf = Box::new(FieldConf {
+        symmetry: Symmetry::None,
+        keep_out_rects: Vec::new(),
+        allowed_region: None,
+        forbidden_regions: Vec::new(),
+        pointer_repulsion: None,
+        density_map: None,
+        wrap: false,
+        max_spawned_chains: DEFAULT_MAX_SPAWNED_CHAINS,
+        max_spawned_triangles: DEFAULT_MAX_SPAWNED_TRIANGLES,
+        next_triangle_stamp: 0,
+        canvas_shape: None,
+        display_scale: DisplayScale {
+            pixels_per_unit: 1.,
+        },
+    });
+    unsafe { FIELD_CONF = Box::into_raw(field_conf) };
 
+    let triangles: Box<Vec<Vec<TriangleHandle>>> =
+        Box::new((0..CHAIN_COUNT).map(|_| Vec::with_capacity(200)).collect());
+    let p: *mut Vec<Vec<TriangleHandle>> = Box::into_raw(triangles);
     unsafe { TRIANGLES = p };
 
     let rng_seed: [u8; 16] = 1u128.to_ne_bytes();
     let rng: Box<Pcg32> = Box::new(Pcg32::from_seed(rng_seed));
     let p: *mut Pcg32 = Box::into_raw(rng);
     unsafe { RNG = p };
-    unsafe { ENVS = Box::into_raw(Box::new(mem::MaybeUninit::uninit().assume_init())) };
 
     let default_conf = Conf {
         prng_seed: 9209.2338,
@@ -323,6 +1461,15 @@ pub fn init_triangles(canvas_width: usize, canvas_height: usize) {
         triangle_border_color: "".into(),
         rotation_offset: 60.0,
         generation_rate: 26.0,
+        fractal_growth: None,
+        flow_field: None,
+        growth_mode: GrowthMode::RandomWalk,
+        gap_fill: None,
+        subdivision: None,
+        image_colors: None,
+        spawn: None,
+        territory: None,
+        leash: None,
     };
     let colors: [(String, String); CHAIN_COUNT] = [
         ("rgb(81, 12, 84)".into(), "rgb(226, 12, 163)".into()),
@@ -331,252 +1478,2465 @@ pub fn init_triangles(canvas_width: usize, canvas_height: usize) {
         // ("rgb(135, 63, 22)".into(), "rgb(255, 144, 6)".into()),
         // ("rgb(125, 33, 33)".into(), "rgb(255, 6, 6)".into()),
     ];
-    for (i, (triangle_color, triangle_border_color)) in colors.iter().enumerate() {
-        let mut conf = default_conf.clone();
-        conf.triangle_color = triangle_color.clone();
-        conf.triangle_border_color = triangle_border_color.clone();
-        unsafe { ptr::write((ENVS as *mut Env).add(i), Env::new(conf, i)) };
-    }
+    let envs: Box<Vec<Env>> = Box::new(
+        (0..CHAIN_COUNT)
+            .map(|i| {
+                let (triangle_color, triangle_border_color) = &colors[i];
+                let mut conf = default_conf.clone();
+                conf.triangle_color = triangle_color.clone();
+                conf.triangle_border_color = triangle_border_color.clone();
+                Env::new(conf, i)
+            })
+            .collect(),
+    );
+    unsafe { ENVS = Box::into_raw(envs) };
 }
 
-#[inline]
-const fn deg_to_rad(degrees: f32) -> f32 {
-    degrees * (f32::consts::PI / 180.0)
+/// Resizes the canvas to `width` by `height` CSS pixels, moving existing triangles according to
+/// `policy`.  Triangles that no longer fit on the canvas or in their chain's territory or collide
+/// after being moved are removed, and symmetric and wrapped copies are rebuilt for the new canvas.
+/// Keep-out rectangles, masks, spawn points and regions, chain territories and leashes, and the
+/// canvas shape are left where they are.  Chains keep growing into any newly available space.  Sizes too small to fit a
+/// triangle with a triangle's width of margin around it are clamped up to that size.
+#[wasm_bindgen]
+pub fn resize(width: usize, height: usize, policy: ResizePolicy) {
+    let display_scale = field_conf().display_scale;
+    resize_world(
+        display_scale.to_world_size(width),
+        display_scale.to_world_size(height),
+        policy,
+    );
 }
 
-#[inline]
-fn min3(a: f32, b: f32, c: f32) -> f32 {
-    a.min(b).min(c)
-}
+/// Sets the display scale to `pixels_per_unit` CSS pixels per world unit.  All positions and sizes
+/// passed to the engine, like triangle sizes, keep-out rectangles, and paths, are in world units,
+/// while the canvas size passed to `init_triangles` and `resize` is in CSS pixels.  Existing
+/// triangles keep their positions in the world and are re-rendered at the new scale, and the
+/// canvas is resized to cover the same area of the page, removing triangles that no longer fit.
+#[wasm_bindgen]
+pub fn set_display_scale(pixels_per_unit: f32) {
+    let conf = unsafe { &(&*ENVS)[0].conf };
+    let old_scale = field_conf().display_scale;
+    let (width, height) = (
+        old_scale.to_pixel_size(conf.canvas_width),
+        old_scale.to_pixel_size(conf.canvas_height),
+    );
+    field_conf().display_scale = DisplayScale {
+        pixels_per_unit: pixels_per_unit.max(0.01),
+    };
 
-#[inline]
-fn max3(a: f32, b: f32, c: f32) -> f32 {
-    a.max(b).max(c)
+    resize(width, height, ResizePolicy::Prune);
+    if let Some(chunked_world) = chunked_world() {
+        chunked_world.clear();
+        update_camera();
+    }
 }
 
-#[inline]
-fn bounds(v1: Point2<f32>, v2: Point2<f32>, v3: Point2<f32>) -> (Point2<f32>, Point2<f32>) {
-    (
-        p2(min3(v1.x, v2.x, v3.x), min3(v1.y, v2.y, v3.y)),
-        p2(max3(v1.x, v2.x, v3.x), max3(v1.y, v2.y, v3.y)),
-    )
+/// Picks a display scale for a viewport of `viewport_width` by `viewport_height` CSS pixels such
+/// that the field covers as many world units as it would on a `reference_width` by
+/// `reference_height` viewport at a scale of 1.  Triangles then fill the same fraction of the
+/// screen with the same number of them on any display.  See `set_display_scale`.
+#[wasm_bindgen]
+pub fn fit_display_scale(
+    viewport_width: f32,
+    viewport_height: f32,
+    reference_width: f32,
+    reference_height: f32,
+) {
+    let pixels_per_unit =
+        ((viewport_width * viewport_height) / (reference_width * reference_height)).sqrt();
+    set_display_scale(pixels_per_unit);
 }
 
-#[inline]
-fn ccw(p1: Point2<f32>, p2: Point2<f32>, p3: Point2<f32>) -> bool {
-    (p3.y - p1.y) * (p2.x - p1.x) >= (p2.y - p1.y) * (p3.x - p1.x)
-}
+/// Resizes the canvas to `width` by `height` world units.  See `resize`.
+fn resize_world(width: usize, height: usize, policy: ResizePolicy) {
+    let envs = unsafe { &mut *ENVS };
+    let min_size = envs
+        .iter()
+        .filter(|env| env.active)
+        .map(|env| (env.conf.triangle_size * 2.).ceil() as usize + 1)
+        .max()
+        .unwrap_or(1);
+    let (width, height) = (width.max(min_size), height.max(min_size));
+    let old_size = Vector2::new(
+        envs[0].conf.canvas_width as f32,
+        envs[0].conf.canvas_height as f32,
+    );
+    let new_size = Vector2::new(width as f32, height as f32);
+    let transform = |pt: Point2<f32>| policy.transform_point(pt, old_size, new_size);
+    // triangles are stretched by `Scale`, so the size of new ones is scaled to match their area
+    let size_scale = match policy {
+        ResizePolicy::Scale => (new_size.x * new_size.y / (old_size.x * old_size.y)).sqrt(),
+        _ => 1.,
+    };
 
-/// adapted from https://stackoverflow.com/a/9997374/3833068
-#[inline]
-fn check_line_seg_intersection(
-    l1p1: Point2<f32>,
-    l1p2: Point2<f32>,
-    l2p1: Point2<f32>,
-    l2p2: Point2<f32>,
-) -> bool {
-    ccw(l1p1, l2p1, l2p2) != ccw(l1p2, l2p1, l2p2) && ccw(l1p1, l1p2, l2p1) != ccw(l1p1, l1p2, l2p2)
-}
+    if let Symmetry::Radial { center, .. } = &mut field_conf().symmetry {
+        *center = transform(*center);
+    }
+    if let Some((position, velocity)) = field_conf()
+        .pointer_repulsion
+        .as_mut()
+        .and_then(|repulsion| repulsion.pointer.as_mut())
+    {
+        *velocity = transform(*position + *velocity) - transform(*position);
+        *position = transform(*position);
+    }
+    for env in envs.iter_mut().filter(|env| env.active) {
+        env.conf.canvas_width = width;
+        env.conf.canvas_height = height;
+        env.set_triangle_size(env.conf.triangle_size * size_scale);
+        env.last_triangle = env.last_triangle.map(transform);
+        env.seed_point = transform(env.seed_point);
+        env.target = env.target.map(transform);
+        env.attractors
+            .iter_mut()
+            .for_each(|pt| *pt = transform(*pt));
+        env.failed_origins.clear();
 
-/// If any side of the first triangle intersects any side of the second triangle, they intersect.
-/// Additionally, if two sides of the first triangle don't intersect, the triangles don't
-/// intersect.
-fn check_triangle_collision(t1: &TriangleBuf, t2: &TriangleBuf) -> bool {
-    for (l1p1, l1p2) in &[(t1[0], t1[1]), (t1[1], t1[2]), (t1[2], t1[0])] {
-        for (l2p1, l2p2) in &[(t2[0], t2[1]), (t2[1], t2[2]), (t2[2], t2[0])] {
-            if check_line_seg_intersection(*l1p1, *l1p2, *l2p1, *l2p2) {
-                return true;
-            }
+        for handle in env.triangles().iter().filter(|handle| !handle.removed) {
+            remove_triangle_geometry(handle);
         }
     }
 
-    false
-}
+    // Triangles are placed back one at a time so that any that collide with ones already placed
+    // after being moved are removed.  Every vertex is moved by the same transform, so neighbors
+    // that shared a vertex before still share it exactly.
+    for env in envs.iter_mut().filter(|env| env.active) {
+        for triangle_ix in 0..env.triangles().len() {
+            let handle = env.get_triangle(triangle_ix);
+            if handle.removed {
+                continue;
+            }
+            let geometry = field_conf().wrap_triangle(handle.geometry.map(transform), &env.conf);
+            let bounding_box = if field_conf().is_triangle_allowed(&geometry, &env.conf)
+                && env.conf.is_in_territory(&geometry)
+            {
+                check_placement_collisions(env, &geometry)
+            } else {
+                None
+            };
+            let bounding_box = match bounding_box {
+                Some(bounding_box) => bounding_box,
+                None => {
+                    unlink_triangle(env, triangle_ix);
+                    continue;
+                }
+            };
 
-struct TriangleCollisionVisitor<'a> {
-    pub triangle: &'a TriangleBuf,
-    pub triangle_bv: &'a AABB<f32>,
-    // pub triangles: &'a [Vec<TriangleHandle>; CHAIN_COUNT],
-    pub does_collide: &'a mut bool,
-}
+            let (size, depth) = (handle.size * size_scale, handle.depth);
+            let moved =
+                build_triangle_handle(env, triangle_ix, bounding_box, geometry, size, depth);
+            let handle = env.get_triangle_mut(triangle_ix);
+            *handle = TriangleHandle {
+                generation: handle.generation,
+                stamp: handle.stamp,
+                prev_node: handle.prev_node,
+                next_node_1: handle.next_node_1,
+                next_node_2: handle.next_node_2,
+                ..moved
+            };
+        }
 
-impl<'a> Visitor<(usize, usize), AABB<f32>> for TriangleCollisionVisitor<'a> {
-    fn visit(&mut self, bv: &AABB<f32>, data: Option<&(usize, usize)>) -> VisitStatus {
-        if let Some(&(chain_ix, triangle_ix)) = data {
-            // We reached a leaf node, so we check to see if our candidate triangle collides with it
-            if check_triangle_collision(
-                &self.triangle,
-                &get_triangle(chain_ix, triangle_ix).geometry,
-            ) {
-                *self.does_collide = true;
-                VisitStatus::ExitEarly
-            } else {
-                VisitStatus::Stop
-            }
-        } else if self.triangle_bv.intersects(bv) {
-            // The BV of our candidate triangle collides with the BV of this level of the BVH
-            VisitStatus::Continue
+        let last_triangle_removed = if env.last_triangle_ix == usize::MAX {
+            !field_conf().is_triangle_allowed(&env.last_triangle, &env.conf)
+                || !env.conf.is_in_territory(&env.last_triangle)
+                || check_world_collision(&env.last_triangle, &get_triangle_bv(&env.last_triangle))
         } else {
-            // Nothing collides, so we bail out of this branch of the tree
-            VisitStatus::Stop
+            env.get_triangle(env.last_triangle_ix).removed
+        };
+        if last_triangle_removed {
+            env.set_new_last_triangle();
         }
     }
 }
 
-/// Attempts to find a valid rotation for the next triangle, returning the proposed triangle if it
-/// is found.
-fn find_triangle_placement(
-    env: &Env,
-    origin: Point2<f32>,
-    rotation: f32,
-) -> Option<(AABB<f32>, TriangleBuf)> {
-    let Env {
-        conf: Conf {
-            max_rotation_rads, ..
-        },
-        base_triangle_coords,
-        ..
-    } = env;
-
-    let proposed_rotation =
-        rotation + rng().gen_range(-*max_rotation_rads, *max_rotation_rads + 0.00001);
-    // determine if this proposed triangle would intersect any other triangle
-    let proposed_isometry = Isometry2::new(Vector2::new(origin.x, origin.y), proposed_rotation);
-    let proposed_triangle = [
-        proposed_isometry * base_triangle_coords[0],
-        proposed_isometry * base_triangle_coords[1],
-        proposed_isometry * base_triangle_coords[2],
-    ];
-    let pt_within_canvas = |pt: &Point2<f32>| {
-        pt.x > 0.
-            && pt.x < env.conf.canvas_width as f32
-            && pt.y > 0.
-            && pt.y < env.conf.canvas_height as f32
-    };
-    if proposed_triangle.iter().any(|pt| !pt_within_canvas(pt)) {
-        return None;
+/// Switches the field to an infinite world made of square chunks `chunk_size` units wide, showing
+/// a viewport of `viewport_width` by `viewport_height` pixels with its top left corner at the
+/// world origin.  The triangles of each chunk are generated as the camera moves near it, and chunks
+/// away from the viewport are evicted once more than `max_chunks` are loaded.  Existing triangles
+/// and spawned chains are removed, and `render`, `generate`, and `spawn_chain` do nothing until
+/// `disable_infinite_world` is called.
+///
+/// The infinite world is a static backdrop rather than an endless canvas.  Each chunk grows one
+/// chain per canvas chain with a plain random walk that only uses the chain's triangle size, count,
+/// rotation settings, and colors.  Growth modes, fractal growth, flow fields, gap filling,
+/// subdivision, image colors, spawn points, territories, and leashes are ignored, as are the
+/// field's symmetry, wrapping, masks, keep-out rectangles, density map, and pointer repulsion.
+/// Chunks don't grow once generated, chains stop at chunk borders, and chunk triangles can't be
+/// hit-tested or queried.
+#[wasm_bindgen]
+pub fn enable_infinite_world(
+    chunk_size: f32,
+    max_chunks: usize,
+    viewport_width: f32,
+    viewport_height: f32,
+) {
+    disable_infinite_world();
+    // spawned chains are removed along with their triangles by `reinitialize_global_state`
+    for env in unsafe { &(&*ENVS)[..CHAIN_COUNT] } {
+        for handle in env.triangles().iter().filter(|handle| !handle.removed) {
+            remove_triangle_geometry(handle);
+        }
+    }
+    reinitialize_global_state();
+    for env in unsafe { (&mut *ENVS).iter_mut() } {
+        env.reset();
     }
-    let bounding_box = get_triangle_bv(&proposed_triangle);
 
-    let mut does_collide = false;
-    let mut visitor = TriangleCollisionVisitor {
-        triangle: &proposed_triangle,
-        triangle_bv: &bounding_box,
-        // triangles: unsafe { &*TRIANGLES },
-        does_collide: &mut does_collide,
+    let camera = Camera {
+        position: Point2::origin(),
+        zoom: 1.,
+        viewport_width,
+        viewport_height,
     };
-    world().visit(&mut visitor);
+    let chunked_world = Box::new(ChunkedWorld::new(chunk_size.max(1.), max_chunks, camera));
+    unsafe { CHUNKED_WORLD = Box::into_raw(chunked_world) };
+    update_camera();
+}
 
-    if !does_collide {
-        // we've found a valid triangle placement
-        Some((bounding_box, proposed_triangle))
-    } else {
-        None
+/// Moves the camera of the infinite world so that the top left corner of the viewport is at world
+/// coordinates `(x, y)`, showing `zoom` pixels per world unit.  The position can be driven by page
+/// scroll to create a parallax background or by dragging to pan around.
+#[wasm_bindgen]
+pub fn set_camera(x: f32, y: f32, zoom: f32) {
+    if let Some(chunked_world) = chunked_world() {
+        chunked_world.camera.position = p2(x, y);
+        chunked_world.camera.zoom = zoom.max(MIN_CAMERA_ZOOM);
+        update_camera();
     }
 }
 
-fn generate_triangle(env: &mut Env) -> Option<(AABB<f32>, TriangleBuf)> {
-    // pick one of the other two vertices to use as the new origin
-    let (ix, rot_offset) = if rng().gen_range(0, 2) == 0 {
-        (1, deg_to_rad(env.conf.rotation_offset))
-    } else {
-        (2, deg_to_rad(-env.conf.rotation_offset))
-    };
-
-    let origin = env.last_triangle[ix];
-    for _ in 0..PLACEMENT_ATTEMPTS {
-        let placement_opt = find_triangle_placement(env, origin, env.rotation + rot_offset);
-        if let Some((bv, triangle)) = placement_opt {
-            env.rotation += rot_offset;
-            return Some((bv, triangle));
-        }
+/// Changes the size in pixels of the viewport that the infinite world is shown in.
+#[wasm_bindgen]
+pub fn set_viewport_size(width: f32, height: f32) {
+    if let Some(chunked_world) = chunked_world() {
+        chunked_world.camera.viewport_width = width;
+        chunked_world.camera.viewport_height = height;
+        update_camera();
     }
-
-    None // failed to place a triangle at this origin in `PLACEMENT_ATTTEMPTS` attempts
 }
 
-fn place_triangle(env: &mut Env, insert_at_oldest_ix: bool) -> Option<()> {
-    for _ in 0..PLACEMENT_BAILOUT_THRESHOLD {
-        if let Some((bv, triangle)) = generate_triangle(env) {
-            let dom_id = render_triangle_array(
-                &triangle,
-                &env.conf.triangle_color,
-                &env.conf.triangle_border_color,
-            );
-            let insertion_ix = if insert_at_oldest_ix {
-                env.oldest_triangle_ix
-            } else {
-                env.triangles().len()
-            };
-            let leaf_id = world().insert(DBVTLeaf::new(bv, (env.chain_ix, insertion_ix)));
-
-            let handle = TriangleHandle {
-                dom_id,
-                collider_handle: leaf_id,
-                geometry: triangle,
-                prev_node: if env.last_triangle_ix == usize::MAX {
-                    None
-                } else {
-                    Some(env.last_triangle_ix)
-                },
-                next_node_1: None,
-                next_node_2: None,
-            };
-            if insert_at_oldest_ix {
-                *env.get_triangle_mut(env.oldest_triangle_ix) = handle;
-            } else {
-                env.triangles_mut().push(handle);
-            }
-
-            if env.last_triangle_ix != usize::MAX {
-                let last_triangle = env.get_triangle_mut(env.last_triangle_ix);
-                match (last_triangle.next_node_1, last_triangle.next_node_2) {
-                    (Some(_), None) => {
-                        last_triangle.next_node_2 = Some(insertion_ix);
-                        debug_assert!(last_triangle.next_node_2 != last_triangle.prev_node);
-                        debug_assert!(last_triangle.next_node_2 != last_triangle.next_node_1);
-                    }
-                    (None, Some(_)) | (None, None) => {
-                        last_triangle.next_node_1 = Some(insertion_ix);
-                        debug_assert!(last_triangle.next_node_1 != last_triangle.prev_node);
-                        debug_assert!(last_triangle.next_node_1 != last_triangle.next_node_2);
-                    }
-                    (Some(_), Some(_)) => {
-                        panic!("Tried to add new triangle to triangle with two children");
-                    }
-                }
-            }
-            env.last_triangle = triangle;
-            env.last_triangle_ix = insertion_ix;
-            return Some(());
-        }
-
-        // we failed to place a triangle at this origin; we have to pick a new origin point.
-        env.set_new_last_triangle();
-    }
+/// Loads the chunks near the camera and points the SVG's view box at the visible area
+fn update_camera() {
+    let chunked_world = match chunked_world() {
+        Some(chunked_world) => chunked_world,
+        None => return,
+    };
+    let confs: Vec<&Conf> = unsafe { (&*ENVS).iter().map(|env| &env.conf).collect() };
+    let pixels_per_unit = field_conf().display_scale.pixels_per_unit;
+    chunked_world.update(&confs, pixels_per_unit);
 
-    None
+    let visible = chunked_world.camera.get_visible_bounds(pixels_per_unit);
+    let (mins, size) = (
+        visible.mins * pixels_per_unit,
+        (visible.maxs - visible.mins) * pixels_per_unit,
+    );
+    set_view_box(mins.x, mins.y, size.x, size.y);
 }
 
+/// Removes all chunks of the infinite world and confines the field to the canvas again, rendering
+/// the chains on the canvas from scratch.
 #[wasm_bindgen]
-pub fn render(chain_ix: usize) {
-    if chain_ix == 0 {
-        reinitialize_global_state();
+pub fn disable_infinite_world() {
+    let chunked_world = unsafe { CHUNKED_WORLD };
+    if chunked_world.is_null() {
+        return;
     }
-    let mut env = unsafe { &mut (*ENVS)[chain_ix] };
+    unsafe { CHUNKED_WORLD = ptr::null_mut() };
 
-    // place `triangle_count` triangles
-    for _ in 0..env.conf.triangle_count {
-        place_triangle(&mut env, false);
+    let mut chunked_world = unsafe { Box::from_raw(chunked_world) };
+    chunked_world.clear();
+    let conf = unsafe { &(&*ENVS)[0].conf };
+    let display_scale = field_conf().display_scale;
+    set_view_box(
+        0.,
+        0.,
+        display_scale.to_pixel_size(conf.canvas_width) as f32,
+        display_scale.to_pixel_size(conf.canvas_height) as f32,
+    );
+    for chain_ix in 0..CHAIN_COUNT {
+        render(chain_ix);
     }
 }
 
-/// Delete the oldest generated triangle and generate a new triangle.
+/// Enables depth-scaled growth for the chain with index `chain_ix`.  Each new triangle will be
+/// `scale_factor` times the size of its parent, randomly perturbed by up to `±scale_jitter` of that,
+/// and branches stop growing once their triangles would be smaller than `min_triangle_size`.
 #[wasm_bindgen]
-pub fn generate(chain_ix: usize) {
+pub fn set_fractal_growth(
+    chain_ix: usize,
+    scale_factor: f32,
+    scale_jitter: f32,
+    min_triangle_size: f32,
+) {
     let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
-    let assert_handle_valid = |handle: &TriangleHandle| {
-        debug_assert!(handle.degree() != 0);
+    env.conf.fractal_growth = Some(FractalGrowthConf {
+        scale_factor,
+        scale_jitter,
+        min_triangle_size,
+    });
+}
+
+/// Restores constant-size growth for the chain with index `chain_ix`.
+#[wasm_bindgen]
+pub fn clear_fractal_growth(chain_ix: usize) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.fractal_growth = None;
+}
+
+/// Colors the triangles of the chain with index `chain_ix` from the PNG image `png_bytes`, stretched
+/// over the rectangle at `(x, y)` with size `width` by `height`.  If `palette_size` is nonzero,
+/// colors are quantized to a palette of that many colors built from the image.  Borders are
+/// lightened by `border_shade` if it is positive or darkened if it is negative.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn set_image_colors(
+    chain_ix: usize,
+    png_bytes: &[u8],
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    sampling: ColorSampling,
+    palette_size: usize,
+    border_shade: f32,
+) -> Result<(), String> {
+    let image = Image::decode_png(png_bytes)?;
+    let palette = if palette_size == 0 {
+        None
+    } else {
+        Some(build_palette(&image, palette_size))
+    };
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.image_colors = Some(ImageColorConf {
+        image: Rc::new(CanvasImage {
+            image,
+            bounds: AABB::new(p2(x, y), p2(x + width, y + height)),
+        }),
+        sampling,
+        palette,
+        border_shade,
+    });
+    Ok(())
+}
+
+/// Restores the fixed colors of the chain with index `chain_ix` for new triangles.
+#[wasm_bindgen]
+pub fn clear_image_colors(chain_ix: usize) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.image_colors = None;
+}
+
+/// Switches the chain with index `chain_ix` to space colonization growth, where it branches from and
+/// grows toward its attractor points.  Attractors are set with `set_attractors` or
+/// `generate_random_attractors`.
+#[wasm_bindgen]
+pub fn set_space_colonization(
+    chain_ix: usize,
+    influence_radius: f32,
+    kill_radius: f32,
+    steering_strength: f32,
+) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.growth_mode = GrowthMode::SpaceColonization {
+        influence_radius,
+        kill_radius,
+        steering_strength: steering_strength.clamp(0., 1.),
+    };
+}
+
+/// Switches the chain with index `chain_ix` to diffusion-limited aggregation, where triangles
+/// random-walk in from the edges of the canvas and stick to the chain where they touch it.
+#[wasm_bindgen]
+pub fn set_diffusion_limited_aggregation(
+    chain_ix: usize,
+    step_size: f32,
+    stick_distance: f32,
+    max_walk_steps: usize,
+) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.growth_mode = GrowthMode::DiffusionLimitedAggregation {
+        step_size,
+        stick_distance,
+        max_walk_steps,
+    };
+    env.steering = None;
+}
+
+/// Switches the chain with index `chain_ix` to growing a Penrose tiling.  Triangles that were placed
+/// before switching aren't Robinson triangles, so this is best called before the chain is rendered.
+#[wasm_bindgen]
+pub fn set_penrose_growth(chain_ix: usize) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.growth_mode = GrowthMode::Penrose;
+    env.steering = None;
+}
+
+/// Switches the chain with index `chain_ix` back to the default random walk growth.
+#[wasm_bindgen]
+pub fn reset_growth_mode(chain_ix: usize) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.growth_mode = GrowthMode::RandomWalk;
+    env.steering = None;
+}
+
+/// Switches the chain with index `chain_ix` to seeking its target point, which is set with
+/// `set_chain_target`.  `branch_candidates` triangles are sampled when picking a branch point, and
+/// `steering_strength` from 0 to 1 controls how sharply the chain turns toward the target.
+#[wasm_bindgen]
+pub fn set_target_seeking(chain_ix: usize, branch_candidates: usize, steering_strength: f32) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.growth_mode = GrowthMode::SeekTarget {
+        branch_candidates: branch_candidates.max(1),
+        steering_strength: steering_strength.clamp(0., 1.),
+    };
+    env.steering = None;
+}
+
+/// Sets the point that the chain with index `chain_ix` grows toward when it is seeking a target.
+/// This can be called every frame with the pointer position to make the chain chase the pointer.
+#[wasm_bindgen]
+pub fn set_chain_target(chain_ix: usize, x: f32, y: f32) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.target = Some(p2(x, y));
+}
+
+/// Removes the target of the chain with index `chain_ix`, which goes back to growing randomly until
+/// a new target is set.
+#[wasm_bindgen]
+pub fn clear_chain_target(chain_ix: usize) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.target = None;
+    env.steering = None;
+}
+
+/// Replaces the attractor points of the chain with index `chain_ix`.  `coords` is a flat list of
+/// `[x1, y1, x2, y2, ...]` pairs.
+#[wasm_bindgen]
+pub fn set_attractors(chain_ix: usize, coords: &[f32]) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.attractors = coords
+        .chunks_exact(2)
+        .map(|coords| p2(coords[0], coords[1]))
+        .collect();
+}
+
+/// Replaces the attractor points of the chain with index `chain_ix` with `count` points scattered
+/// uniformly across the canvas or canvas shape using the seeded PRNG.
+#[wasm_bindgen]
+pub fn generate_random_attractors(chain_ix: usize, count: usize) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    let (width, height) = (env.conf.canvas_width as f32, env.conf.canvas_height as f32);
+    let canvas = AABB::new(p2(0., 0.), p2(width, height));
+    env.attractors = (0..count)
+        .map(|_| field_conf().get_random_canvas_point(&canvas))
+        .collect();
+}
+
+fn set_chain_spawn(chain_ix: usize, spawn: Option<Spawn>) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.spawn = spawn;
+    // move the seed of a chain that hasn't placed its first triangle yet
+    if env.last_triangle_ix == usize::MAX {
+        env.reseed();
+    }
+}
+
+/// Seeds the chain with index `chain_ix` at `(x, y)`, growing away from it in the direction
+/// rotated clockwise by `rotation_degrees` from straight down.  The chain also restarts from there
+/// whenever it runs out of triangles.  If the spot is blocked, the closest free spot found by
+/// searching outward from it is used instead.
+#[wasm_bindgen]
+pub fn set_chain_seed(chain_ix: usize, x: f32, y: f32, rotation_degrees: f32) {
+    set_chain_spawn(
+        chain_ix,
+        Some(Spawn::Point {
+            position: p2(x, y),
+            rotation: deg_to_rad(rotation_degrees),
+        }),
+    );
+}
+
+/// Makes the chain with index `chain_ix` start and restart at random spots inside of the rectangle
+/// at `(x, y)` with size `width` by `height`.  If the whole rectangle is blocked, for example by a
+/// keep-out rectangle over the same area, the chain starts from next to it instead.
+#[wasm_bindgen]
+pub fn set_chain_spawn_rect(chain_ix: usize, x: f32, y: f32, width: f32, height: f32) {
+    let rect = AABB::new(p2(x, y), p2(x + width, y + height));
+    set_chain_spawn(
+        chain_ix,
+        Some(Spawn::Region(Rc::new(Mask::rounded_rect(&rect, 0.)))),
+    );
+}
+
+/// Makes the chain with index `chain_ix` start and restart at random spots inside of the region
+/// filled by the SVG path data `d`, after scaling its coordinates by `scale` and offsetting them by
+/// `(x, y)`.  See `set_chain_spawn_rect`.
+#[wasm_bindgen]
+pub fn set_chain_spawn_path(
+    chain_ix: usize,
+    d: &str,
+    fill_rule: FillRule,
+    x: f32,
+    y: f32,
+    scale: f32,
+) -> Result<(), String> {
+    let region = Mask::from_svg_path(d, fill_rule, Vector2::new(x, y), scale)?;
+    if region.polygons.is_empty() {
+        return Err("Spawn path doesn't enclose any area".into());
+    }
+    set_chain_spawn(chain_ix, Some(Spawn::Region(Rc::new(region))));
+    Ok(())
+}
+
+/// Makes the chain with index `chain_ix` start at random spots on the canvas again.
+#[wasm_bindgen]
+pub fn clear_chain_spawn(chain_ix: usize) {
+    set_chain_spawn(chain_ix, None);
+}
+
+/// Returns an error if `bounds` are too small to seed a chain's triangle in with a triangle's width
+/// of margin around it, like `get_random_initial_triangle` needs
+fn check_fits_triangle(conf: &Conf, bounds: &AABB<f32>, name: &str) -> Result<(), String> {
+    let extents = bounds.maxs - bounds.mins;
+    if extents.x > conf.triangle_size * 2. && extents.y > conf.triangle_size * 2. {
+        Ok(())
+    } else {
+        Err(format!("{} is too small to fit a triangle", name))
+    }
+}
+
+fn set_chain_territory(chain_ix: usize, territory: Option<Mask>) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.territory = territory.map(Rc::new);
+    if let Some(territory) = env.conf.territory.clone() {
+        prune_chain_triangles(env, false, |triangle| {
+            !territory.contains_triangle(triangle)
+        });
+    }
+}
+
+/// Confines the chain with index `chain_ix` to the rectangle at `(x, y)` with size `width` by
+/// `height` and removes any of its existing triangles outside of it.  Symmetric and wrapped copies
+/// of the chain's triangles may still extend outside of it.  Returns an error if the rectangle is
+/// less than two triangle sizes wide or tall.
+#[wasm_bindgen]
+pub fn set_chain_territory_rect(
+    chain_ix: usize,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) -> Result<(), String> {
+    let rect = AABB::new(p2(x, y), p2(x + width, y + height));
+    let territory = Mask::rounded_rect(&rect, 0.);
+    let conf = unsafe { &(&*ENVS)[chain_ix].conf };
+    check_fits_triangle(conf, &territory.bounds, "Territory")?;
+    set_chain_territory(chain_ix, Some(territory));
+    Ok(())
+}
+
+/// Confines the chain with index `chain_ix` to the polygon with vertices `coords`, a flat list of
+/// `[x1, y1, x2, y2, ...]` pairs.  See `set_chain_territory_rect`.
+#[wasm_bindgen]
+pub fn set_chain_territory_polygon(chain_ix: usize, coords: &[f32]) -> Result<(), String> {
+    let territory =
+        build_polygon_mask(coords).ok_or("Territory polygon must have at least 3 vertices")?;
+    let conf = unsafe { &(&*ENVS)[chain_ix].conf };
+    check_fits_triangle(conf, &territory.bounds, "Territory")?;
+    set_chain_territory(chain_ix, Some(territory));
+    Ok(())
+}
+
+/// Lets the chain with index `chain_ix` grow anywhere on the canvas again.
+#[wasm_bindgen]
+pub fn clear_chain_territory(chain_ix: usize) {
+    set_chain_territory(chain_ix, None);
+}
+
+/// Keeps the triangles of the chain with index `chain_ix` within `radius` of the point that the
+/// chain was seeded at and removes any of its existing triangles further out.  When the chain runs
+/// out of triangles, it restarts inside of the same circle.  Returns an error if `radius` isn't
+/// larger than the chain's triangle size.
+#[wasm_bindgen]
+pub fn set_chain_leash(chain_ix: usize, radius: f32) -> Result<(), String> {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    let leash = Leash {
+        center: env.seed_point,
+        radius,
+    };
+    check_fits_triangle(&env.conf, &leash.get_bounds(), "Leash")?;
+    env.conf.leash = Some(leash);
+    prune_chain_triangles(env, false, |triangle| !leash.contains_triangle(triangle));
+    Ok(())
+}
+
+/// Lets the chain with index `chain_ix` grow any distance from its seed point again.
+#[wasm_bindgen]
+pub fn clear_chain_leash(chain_ix: usize) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.leash = None;
+}
+
+/// Adds a temporary chain seeded at or near `(x, y)` that grows with the settings of the first
+/// chain but in the colors `color` and `border_color`, returning its chain index.  If the spot is
+/// taken, a few spots further out are tried.  The chain grows by one triangle each time `generate`
+/// is called for it, or `generate_spawned_chains` is called, until it reaches the size of the first
+/// chain or the room left under the limits set by `set_spawned_chain_limits`.  After being
+/// generated `lifetime` times it ends as determined by `expiry` and its index is freed for reuse.
+/// Returns `None` if the limits have been reached or no free spot was found.
+#[wasm_bindgen]
+pub fn spawn_chain(
+    x: f32,
+    y: f32,
+    color: &str,
+    border_color: &str,
+    lifetime: usize,
+    expiry: ChainExpiry,
+) -> Option<usize> {
+    if chunked_world().is_some() {
+        return None;
+    }
+    let envs = unsafe { &mut *ENVS };
+    let (spawned_count, spawned_triangle_count) = envs[CHAIN_COUNT..]
+        .iter()
+        .filter(|env| env.active)
+        .fold((0, 0), |(count, triangle_count), env| {
+            (count + 1, triangle_count + env.conf.triangle_count)
+        });
+    let triangle_count = envs[0].conf.triangle_count.min(
+        field_conf()
+            .max_spawned_triangles
+            .saturating_sub(spawned_triangle_count),
+    );
+    if spawned_count >= field_conf().max_spawned_chains || triangle_count == 0 {
+        return None;
+    }
+
+    let position = p2(x, y);
+    let rotation = rng().gen_range(0., f32::consts::TAU);
+    let conf = Conf {
+        triangle_count,
+        triangle_color: color.into(),
+        triangle_border_color: border_color.into(),
+        image_colors: None,
+        spawn: Some(Spawn::Point { position, rotation }),
+        territory: None,
+        leash: None,
+        ..envs[0].conf.clone()
+    };
+    let chain_ix = envs[CHAIN_COUNT..]
+        .iter()
+        .position(|env| !env.active)
+        .map_or(envs.len(), |ix| CHAIN_COUNT + ix);
+    let mut env = Env::new_unseeded(conf, chain_ix);
+    let (triangle, rotation) = search_for_seed(
+        &env.conf,
+        &env.base_triangle_coords,
+        position,
+        rotation,
+        SPAWN_CHAIN_SEARCH_ATTEMPTS,
+    )?;
+    env.set_seed(triangle, rotation);
+    env.lifetime = Some(ChainLifetime {
+        remaining: lifetime,
+        expiry,
+    });
+
+    if chain_ix == envs.len() {
+        envs.push(env);
+        unsafe { (&mut *TRIANGLES).push(Vec::new()) };
+    } else {
+        envs[chain_ix] = env;
+    }
+    Some(chain_ix)
+}
+
+/// Calls `generate` for every spawned chain.
+#[wasm_bindgen]
+pub fn generate_spawned_chains() {
+    for chain_ix in CHAIN_COUNT..unsafe { (&*ENVS).len() } {
+        generate(chain_ix);
+    }
+}
+
+/// Removes all spawned chains and their triangles right away.
+#[wasm_bindgen]
+pub fn clear_spawned_chains() {
+    for chain_ix in CHAIN_COUNT..unsafe { (&*ENVS).len() } {
+        if unsafe { (&*ENVS)[chain_ix].active } {
+            end_spawned_chain(chain_ix, ChainExpiry::Remove);
+        }
+    }
+}
+
+/// Limits the number of chains that can be spawned with `spawn_chain` at once to `max_chains` and
+/// the number of triangles that they can grow to in total to `max_triangles`.  Chains that have
+/// already been spawned are kept even if they exceed the new limits.
+#[wasm_bindgen]
+pub fn set_spawned_chain_limits(max_chains: usize, max_triangles: usize) {
+    field_conf().max_spawned_chains = max_chains;
+    field_conf().max_spawned_triangles = max_triangles;
+}
+
+/// Ends the spawned chain with index `chain_ix` according to `expiry`, freeing its index
+fn end_spawned_chain(chain_ix: usize, expiry: ChainExpiry) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    let merge_target = match expiry {
+        ChainExpiry::Merge => find_closest_permanent_chain(env.seed_point),
+        ChainExpiry::Remove => None,
+    };
+    match merge_target {
+        Some(target_ix) => merge_chain(chain_ix, target_ix),
+        None => {
+            for handle in env.triangles().iter().filter(|handle| !handle.removed) {
+                remove_triangle_geometry(handle);
+            }
+            env.triangles_mut().clear();
+        }
+    }
+    env.active = false;
+    env.lifetime = None;
+}
+
+/// Returns the index of the permanent chain with the triangle closest to `pt`, or `None` if none of
+/// them have any triangles
+fn find_closest_permanent_chain(pt: Point2<f32>) -> Option<usize> {
+    (0..CHAIN_COUNT)
+        .filter_map(|chain_ix| {
+            triangles(chain_ix)
+                .iter()
+                .filter(|handle| !handle.removed)
+                .map(|handle| distance_to_triangle(pt, &handle.geometry))
+                .min_by(f32::total_cmp)
+                .map(|distance| (chain_ix, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(chain_ix, _)| chain_ix)
+}
+
+/// Moves all live triangles of the chain with index `chain_ix` into the chain with index
+/// `target_ix`, keeping their links to each other and their ages.  They fill the target chain's
+/// removed slots first and get new slots past its last one once those run out, and removed slots
+/// left at the end of the target are dropped so that repeated merges don't keep growing it.  The
+/// merged triangles are pruned along with the target's own.
+fn merge_chain(chain_ix: usize, target_ix: usize) {
+    let (generation, target_generation) = unsafe {
+        (
+            (&*ENVS)[chain_ix].generation,
+            (&*ENVS)[target_ix].generation,
+        )
+    };
+    let target = unsafe { &mut (&mut *ENVS)[target_ix] };
+    let handles = mem::take(triangles_mut(chain_ix));
+    let mut free_slots = target
+        .triangles()
+        .iter()
+        .enumerate()
+        .filter(|&(ix, handle)| handle.removed && ix != target.oldest_triangle_ix)
+        .map(|(ix, _)| ix)
+        .chain(target.triangles().len()..);
+    // slot in the target chain of each merged triangle, or `None` for removed ones
+    let merged_ixs: Vec<Option<usize>> = handles
+        .iter()
+        .map(|handle| {
+            if handle.removed {
+                None
+            } else {
+                free_slots.next()
+            }
+        })
+        .collect();
+
+    for (mut handle, merged_ix) in handles.into_iter().zip(&merged_ixs) {
+        let triangle_ix = match merged_ix {
+            Some(triangle_ix) => *triangle_ix,
+            None => continue,
+        };
+        world().remove(handle.collider_handle);
+        handle.collider_handle = world().insert(DBVTLeaf::new(
+            get_triangle_bv(&handle.geometry),
+            (target_ix, triangle_ix, 0),
+        ));
+        for (copy_ix, copy) in handle.copies.iter_mut().enumerate() {
+            world().remove(copy.collider_handle);
+            copy.collider_handle = world().insert(DBVTLeaf::new(
+                get_triangle_bv(&copy.geometry),
+                (target_ix, triangle_ix, copy_ix + 1),
+            ));
+        }
+        for link in [
+            &mut handle.prev_node,
+            &mut handle.next_node_1,
+            &mut handle.next_node_2,
+        ] {
+            *link = link.map(|linked_ix| {
+                merged_ixs[linked_ix].expect("Merged triangle is linked to a removed triangle")
+            });
+        }
+        handle.generation = target_generation.saturating_sub(generation - handle.generation);
+        store_triangle_handle(target, triangle_ix, handle);
+    }
+    target.trim_removed_slots();
+}
+
+/// Enables a gap-filling pass for the chain with index `chain_ix`.  When no full-sized triangle can
+/// be placed, the regions where placement failed are retried with triangles shrunk by
+/// `shrink_factor` at each step down to `min_triangle_size`, attaching them to the nearest
+/// triangle of the chain.
+#[wasm_bindgen]
+pub fn set_gap_fill(chain_ix: usize, shrink_factor: f32, min_triangle_size: f32) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.gap_fill = Some(GapFillConf {
+        shrink_factor: shrink_factor.clamp(0.05, 0.95),
+        min_triangle_size,
+    });
+}
+
+/// Disables the gap-filling pass for the chain with index `chain_ix`.
+#[wasm_bindgen]
+pub fn clear_gap_fill(chain_ix: usize) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.gap_fill = None;
+    env.failed_origins.clear();
+}
+
+/// Makes triangles of the chain with index `chain_ix` subdivide in place, one every `interval`
+/// generations, until their children would be smaller than `min_triangle_size`.
+#[wasm_bindgen]
+pub fn set_subdivision(
+    chain_ix: usize,
+    pattern: SubdivisionPattern,
+    interval: usize,
+    min_triangle_size: f32,
+) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.subdivision = Some(SubdivisionConf {
+        pattern,
+        interval: interval.max(1),
+        min_triangle_size,
+    });
+}
+
+/// Stops triangles of the chain with index `chain_ix` from subdividing.
+#[wasm_bindgen]
+pub fn clear_subdivision(chain_ix: usize) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.subdivision = None;
+}
+
+/// Mirrors all newly placed triangles left-to-right across the center of the canvas.
+#[wasm_bindgen]
+pub fn set_horizontal_mirror_symmetry() {
+    field_conf().symmetry = Symmetry::MirrorHorizontal;
+}
+
+/// Mirrors all newly placed triangles top-to-bottom across the center of the canvas.
+#[wasm_bindgen]
+pub fn set_vertical_mirror_symmetry() {
+    field_conf().symmetry = Symmetry::MirrorVertical;
+}
+
+/// Replicates all newly placed triangles `fold` times around the point `(center_x, center_y)`.
+#[wasm_bindgen]
+pub fn set_radial_symmetry(fold: usize, center_x: f32, center_y: f32) {
+    field_conf().symmetry = if fold > 1 {
+        Symmetry::Radial {
+            fold,
+            center: p2(center_x, center_y),
+        }
+    } else {
+        Symmetry::None
+    };
+}
+
+/// Disables symmetry for newly placed triangles.  Existing copies are kept until their triangles
+/// are pruned.
+#[wasm_bindgen]
+pub fn clear_symmetry() {
+    field_conf().symmetry = Symmetry::None;
+}
+
+/// Removes every triangle for which `should_remove` returns `true` for it or any of its copies,
+/// moving chains that were about to branch from a removed triangle to a new branch point.  Removed
+/// slots are refilled over the following generations as the chains keep growing.
+fn prune_triangles(should_remove: impl Fn(&TriangleBuf) -> bool) {
+    let envs = unsafe { &mut *ENVS };
+    for env in envs.iter_mut().filter(|env| env.active) {
+        prune_chain_triangles(env, true, &should_remove);
+    }
+}
+
+/// Removes every triangle of a single chain for which `should_remove` returns `true`, checking its
+/// copies as well if `check_copies` is set.  See `prune_triangles`.
+fn prune_chain_triangles(
+    env: &mut Env,
+    check_copies: bool,
+    should_remove: impl Fn(&TriangleBuf) -> bool,
+) {
+    for triangle_ix in 0..env.triangles().len() {
+        let handle = env.get_triangle(triangle_ix);
+        let copy_count = if check_copies { handle.copies.len() } else { 0 };
+        let overlaps = !handle.removed
+            && (0..=copy_count).any(|copy_ix| should_remove(handle.get_geometry(copy_ix)));
+        if overlaps {
+            remove_triangle(env, triangle_ix);
+        }
+    }
+
+    replace_removed_last_triangle(env, should_remove);
+}
+
+/// Picks a new triangle for the chain to grow from if its last triangle has been removed, or if
+/// `should_remove` returns `true` for its seed triangle when it hasn't placed any triangles yet.
+fn replace_removed_last_triangle(env: &mut Env, should_remove: impl Fn(&TriangleBuf) -> bool) {
+    let last_triangle_removed = if env.last_triangle_ix == usize::MAX {
+        should_remove(&env.last_triangle)
+    } else {
+        env.get_triangle(env.last_triangle_ix).removed
+    };
+    if last_triangle_removed {
+        env.set_new_last_triangle();
+    }
+}
+
+/// Adds a rectangle that triangles will be kept out of and removes any existing triangles that
+/// overlap it, returning an id that can be used to update or remove it.
+#[wasm_bindgen]
+pub fn add_keep_out_rect(x: f32, y: f32, width: f32, height: f32) -> usize {
+    let rect = AABB::new(p2(x, y), p2(x + width, y + height));
+    let keep_out_rects = &mut field_conf().keep_out_rects;
+    keep_out_rects.push(Some(rect));
+    let rect_id = keep_out_rects.len() - 1;
+    prune_triangles(|triangle| check_rect_collision(triangle, &rect));
+    rect_id
+}
+
+/// Moves or resizes the keep-out rectangle with id `rect_id`, removing any existing triangles that
+/// overlap its new bounds.  Returns an error if there is no keep-out rectangle with that id, for
+/// example because it has been removed.
+#[wasm_bindgen]
+pub fn update_keep_out_rect(
+    rect_id: usize,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) -> Result<(), String> {
+    let rect = AABB::new(p2(x, y), p2(x + width, y + height));
+    match field_conf().keep_out_rects.get_mut(rect_id) {
+        Some(Some(existing)) => *existing = rect,
+        _ => return Err(format!("No keep-out rectangle with id {}", rect_id)),
+    }
+    prune_triangles(|triangle| check_rect_collision(triangle, &rect));
+    Ok(())
+}
+
+/// Removes the keep-out rectangle with id `rect_id`, allowing triangles to grow into its area
+/// again.  Returns `false` if there was no keep-out rectangle with that id.
+#[wasm_bindgen]
+pub fn remove_keep_out_rect(rect_id: usize) -> bool {
+    field_conf()
+        .keep_out_rects
+        .get_mut(rect_id)
+        .and_then(Option::take)
+        .is_some()
+}
+
+/// Removes all keep-out rectangles.  Their ids aren't handed out again.
+#[wasm_bindgen]
+pub fn clear_keep_out_rects() {
+    field_conf()
+        .keep_out_rects
+        .iter_mut()
+        .for_each(|rect| *rect = None);
+}
+
+/// Keeps triangles more than `radius` away from the pointer, whose position is fed in with
+/// `update_pointer`.  While the pointer is moving, the repelled area also stretches ahead of it
+/// along the path it will cover in the next `lead_time` seconds.  Returns an error if `radius`
+/// isn't a positive number.
+#[wasm_bindgen]
+pub fn set_pointer_repulsion(radius: f32, lead_time: f32) -> Result<(), String> {
+    if !radius.is_finite() || radius <= 0. {
+        return Err("Pointer repulsion radius must be a positive number".into());
+    }
+    let pointer = field_conf()
+        .pointer_repulsion
+        .and_then(|repulsion| repulsion.pointer);
+    field_conf().pointer_repulsion = Some(PointerRepulsionConf {
+        radius,
+        lead_time: lead_time.max(0.),
+        pointer,
+    });
+    repel_from_pointer();
+    Ok(())
+}
+
+/// Moves the pointer to `(x, y)` with a velocity of `(velocity_x, velocity_y)` units per second,
+/// removing the triangles that are now too close to it.  Branches are retracted from their tips
+/// inward where they end inside of the repelled area.  Branches that continue past it are cut
+/// there, with the parts beyond it left in place to keep growing on their own, and chains regrow
+/// into the area once the pointer has moved on.  Does nothing unless pointer repulsion is
+/// enabled with `set_pointer_repulsion`.
+#[wasm_bindgen]
+pub fn update_pointer(x: f32, y: f32, velocity_x: f32, velocity_y: f32) {
+    if let Some(repulsion) = &mut field_conf().pointer_repulsion {
+        repulsion.pointer = Some((p2(x, y), Vector2::new(velocity_x, velocity_y)));
+        repel_from_pointer();
+    }
+}
+
+/// Stops repelling triangles until the pointer is updated again, for example when it leaves the
+/// page.
+#[wasm_bindgen]
+pub fn clear_pointer() {
+    if let Some(repulsion) = &mut field_conf().pointer_repulsion {
+        repulsion.pointer = None;
+    }
+}
+
+/// Lets triangles grow under the pointer again.
+#[wasm_bindgen]
+pub fn clear_pointer_repulsion() {
+    field_conf().pointer_repulsion = None;
+}
+
+fn repel_from_pointer() {
+    let repulsion = match field_conf().pointer_repulsion {
+        Some(repulsion) => repulsion,
+        None => return,
+    };
+    let (position, velocity) = match repulsion.pointer {
+        Some(pointer) => pointer,
+        None => return,
+    };
+    let end = position + velocity * repulsion.lead_time;
+    let reach = Vector2::new(repulsion.radius, repulsion.radius);
+    let bounds = AABB::new(position.inf(&end) - reach, position.sup(&end) + reach);
+    let mut repelled = query_triangles(&bounds, |triangle| repulsion.intersects_triangle(triangle));
+    // a triangle is repelled if any of its copies are, and its copies are listed next to it
+    repelled.dedup_by_key(|&mut (chain_ix, triangle_ix, _)| (chain_ix, triangle_ix));
+
+    let envs = unsafe { &mut *ENVS };
+    for env in envs.iter_mut().filter(|env| env.active) {
+        let mut remaining: Vec<usize> = repelled
+            .iter()
+            .filter(|&&(chain_ix, _, _)| chain_ix == env.chain_ix)
+            .map(|&(_, triangle_ix, _)| triangle_ix)
+            .collect();
+        // Tips of branches are removed first, which can turn the triangles they grew from into
+        // tips as well.  A branch is only cut once none of the remaining triangles are tips.
+        while !remaining.is_empty() {
+            let tip = remaining
+                .iter()
+                .position(|&triangle_ix| env.get_triangle(triangle_ix).degree() <= 1);
+            let triangle_ix = remaining.swap_remove(tip.unwrap_or(0));
+            remove_triangle(env, triangle_ix);
+        }
+        replace_removed_last_triangle(env, |triangle| repulsion.intersects_triangle(triangle));
+    }
+}
+
+/// Uses the grayscale PNG image `png_bytes`, stretched over the rectangle at `(x, y)` with size
+/// `width` by `height`, as a density map.  Triangles are placed densely over dark areas of the image
+/// and sparsely over light ones, where placements are accepted with probability `min_density`.
+#[wasm_bindgen]
+pub fn set_density_map(
+    png_bytes: &[u8],
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    min_density: f32,
+) -> Result<(), String> {
+    let image = Image::decode_png(png_bytes)?;
+    let size_scale = field_conf()
+        .density_map
+        .as_ref()
+        .and_then(|density_map| density_map.size_scale);
+    field_conf().density_map = Some(DensityMapConf {
+        image: CanvasImage {
+            image,
+            bounds: AABB::new(p2(x, y), p2(x + width, y + height)),
+        },
+        // seeding new chains relies on placements eventually being accepted everywhere
+        min_density: min_density.clamp(0.01, 1.),
+        size_scale,
+    });
+    Ok(())
+}
+
+/// Scales triangles placed over black areas of the density map by `dark_scale` and those over white
+/// areas by `light_scale`, interpolating in between.  Has no effect until a density map is set.
+#[wasm_bindgen]
+pub fn set_density_map_size_scale(dark_scale: f32, light_scale: f32) {
+    if let Some(density_map) = &mut field_conf().density_map {
+        density_map.size_scale = Some((dark_scale, light_scale));
+    }
+}
+
+/// Removes the density map, placing triangles with equal probability and size everywhere again.
+#[wasm_bindgen]
+pub fn clear_density_map() {
+    field_conf().density_map = None;
+}
+
+/// Enables or disables wrapping at the edges of the canvas.  Only triangles placed after this is
+/// called get wrapped copies.
+#[wasm_bindgen]
+pub fn set_wrap(wrap: bool) {
+    field_conf().wrap = wrap;
+}
+
+fn set_canvas_shape(shape: Mask) {
+    field_conf().canvas_shape = Some(shape.clone());
+    if !field_conf().wrap {
+        prune_triangles(|triangle| !shape.contains_triangle(triangle));
+    }
+}
+
+/// Confines triangles to the circle centered at `(cx, cy)` with radius `radius` instead of the
+/// canvas rectangle and removes any existing triangles outside of it.
+#[wasm_bindgen]
+pub fn set_canvas_circle(cx: f32, cy: f32, radius: f32) {
+    set_canvas_shape(Mask::ellipse(p2(cx, cy), Vector2::new(radius, radius)));
+}
+
+/// Confines triangles to the ellipse centered at `(cx, cy)` with radii `rx` and `ry` instead of
+/// the canvas rectangle and removes any existing triangles outside of it.
+#[wasm_bindgen]
+pub fn set_canvas_ellipse(cx: f32, cy: f32, rx: f32, ry: f32) {
+    set_canvas_shape(Mask::ellipse(p2(cx, cy), Vector2::new(rx, ry)));
+}
+
+/// Confines triangles to the rectangle at `(x, y)` with size `width` by `height` and corners
+/// rounded with radius `radius` instead of the canvas rectangle, and removes any existing
+/// triangles outside of it.
+#[wasm_bindgen]
+pub fn set_canvas_rounded_rect(x: f32, y: f32, width: f32, height: f32, radius: f32) {
+    let rect = AABB::new(p2(x, y), p2(x + width, y + height));
+    set_canvas_shape(Mask::rounded_rect(&rect, radius));
+}
+
+/// Confines triangles to the polygon with vertices `coords` instead of the canvas rectangle and
+/// removes any existing triangles outside of it.  `coords` is a flat list of
+/// `[x1, y1, x2, y2, ...]` pairs.
+#[wasm_bindgen]
+pub fn set_canvas_polygon(coords: &[f32]) -> Result<(), String> {
+    let shape = build_polygon_mask(coords).ok_or("Canvas polygon must have at least 3 vertices")?;
+    set_canvas_shape(shape);
+    Ok(())
+}
+
+/// Confines triangles to the canvas rectangle again.
+#[wasm_bindgen]
+pub fn clear_canvas_shape() {
+    field_conf().canvas_shape = None;
+}
+
+/// Returns every live triangle and copy in the field in the order that they were rendered
+fn get_rendered_triangles() -> Vec<export::RenderedTriangle<'static>> {
+    let mut rendered: Vec<(usize, export::RenderedTriangle)> = Vec::new();
+    for chain_triangles in unsafe { (&*TRIANGLES).iter() } {
+        for handle in chain_triangles.iter().filter(|handle| !handle.removed) {
+            rendered.push((
+                handle.dom_id,
+                export::RenderedTriangle {
+                    geometry: &handle.geometry,
+                    color: &handle.color,
+                    border_color: &handle.border_color,
+                },
+            ));
+            for copy in &handle.copies {
+                rendered.push((
+                    copy.dom_id,
+                    export::RenderedTriangle {
+                        geometry: &copy.geometry,
+                        color: &copy.color,
+                        border_color: &copy.border_color,
+                    },
+                ));
+            }
+        }
+    }
+    rendered.sort_by_key(|(dom_id, _)| *dom_id);
+    rendered.into_iter().map(|(_, triangle)| triangle).collect()
+}
+
+/// Exports the current state of the field as an SVG image the size of the canvas.  If wrapping is
+/// enabled, the image tiles seamlessly and can be used as a repeating CSS `background-image`.
+#[wasm_bindgen]
+pub fn export_svg_tile() -> String {
+    let conf = unsafe { &(&*ENVS)[0].conf };
+    let display_scale = field_conf().display_scale;
+    export::build_svg(
+        display_scale.to_pixel_size(conf.canvas_width),
+        display_scale.to_pixel_size(conf.canvas_height),
+        display_scale.pixels_per_unit,
+        &get_rendered_triangles(),
+    )
+}
+
+/// Exports the current state of the field as a PNG image the size of the canvas in device pixels,
+/// with `device_pixel_ratio` device pixels per CSS pixel so that it is sharp on high density
+/// displays.  If wrapping is enabled, the image tiles seamlessly and can be used as a repeating CSS
+/// `background-image`.
+#[wasm_bindgen]
+pub fn export_png_tile(device_pixel_ratio: f32) -> Result<Vec<u8>, String> {
+    let conf = unsafe { &(&*ENVS)[0].conf };
+    let device_pixel_ratio = device_pixel_ratio.max(0.01);
+    let device_pixels_per_unit = field_conf().display_scale.pixels_per_unit * device_pixel_ratio;
+    export::build_png(
+        (conf.canvas_width as f32 * device_pixels_per_unit).round() as usize,
+        (conf.canvas_height as f32 * device_pixels_per_unit).round() as usize,
+        device_pixels_per_unit,
+        device_pixel_ratio,
+        &get_rendered_triangles(),
+    )
+}
+
+/// A placed triangle as reported by `triangle_at`
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TriangleInfo {
+    pub chain_ix: usize,
+    /// Index of the triangle's slot in its chain.  Slots are reused after their triangles are
+    /// removed, so an id only identifies a triangle together with its `stamp`.
+    pub triangle_id: usize,
+    /// Number given to the triangle when it was placed that no other triangle has, so a triangle
+    /// later placed into the same slot has a different stamp
+    pub stamp: usize,
+    /// Number of ancestors between the triangle and the root of the chain it was grown from
+    pub depth: usize,
+    /// Number of generations of its chain since the triangle was placed
+    pub age: usize,
+}
+
+/// Returns the triangle covering the point `(x, y)` in world units, or `None` if there is none.
+/// Points on symmetric or wrapped copies report the triangle that they were copied from.  Triangles
+/// of the infinite world can't be hit-tested.
+#[wasm_bindgen]
+pub fn triangle_at(x: f32, y: f32) -> Option<TriangleInfo> {
+    let (chain_ix, triangle_ix, _) = find_triangle_at(p2(x, y))?;
+    let env = unsafe { &(&*ENVS)[chain_ix] };
+    let handle = env.get_triangle(triangle_ix);
+    Some(TriangleInfo {
+        chain_ix,
+        triangle_id: triangle_ix,
+        stamp: handle.stamp,
+        depth: handle.depth,
+        age: env.generation - handle.generation,
+    })
+}
+
+/// A triangle or one of its copies found by a spatial query
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct QueriedTriangle {
+    pub chain_ix: usize,
+    /// See `TriangleInfo::triangle_id`
+    pub triangle_id: usize,
+    /// See `TriangleInfo::stamp`
+    pub stamp: usize,
+    /// 0 for the triangle itself or `n` for its `n`th symmetric or wrapped copy
+    pub copy_ix: usize,
+    geometry: TriangleBuf,
+}
+
+#[wasm_bindgen]
+impl QueriedTriangle {
+    /// Returns the vertices of the triangle in world units as `[x1, y1, x2, y2, x3, y3]`
+    pub fn coords(&self) -> Vec<f32> {
+        self.geometry.iter().flat_map(|pt| [pt.x, pt.y]).collect()
+    }
+}
+
+fn get_queried_triangles(
+    leaves: impl IntoIterator<Item = (usize, usize, usize)>,
+) -> Vec<QueriedTriangle> {
+    leaves
+        .into_iter()
+        .map(|(chain_ix, triangle_ix, copy_ix)| {
+            let handle = get_triangle(chain_ix, triangle_ix);
+            QueriedTriangle {
+                chain_ix,
+                triangle_id: triangle_ix,
+                stamp: handle.stamp,
+                copy_ix,
+                geometry: *handle.get_geometry(copy_ix),
+            }
+        })
+        .collect()
+}
+
+/// Returns every triangle and copy that intersects the rectangle at `(x, y)` with size `width` by
+/// `height`.  Like `triangle_at`, spatial queries only cover the finite field.
+#[wasm_bindgen]
+pub fn triangles_in_rect(x: f32, y: f32, width: f32, height: f32) -> Vec<QueriedTriangle> {
+    let rect = AABB::new(p2(x, y), p2(x + width, y + height));
+    get_queried_triangles(query_triangles(&rect, |triangle| {
+        check_rect_collision(triangle, &rect)
+    }))
+}
+
+/// Returns every triangle and copy with any part within `radius` of `(x, y)`.
+#[wasm_bindgen]
+pub fn triangles_in_radius(x: f32, y: f32, radius: f32) -> Vec<QueriedTriangle> {
+    let center = p2(x, y);
+    let extents = Vector2::new(radius, radius);
+    let bounds = AABB::new(center - extents, center + extents);
+    get_queried_triangles(query_triangles(&bounds, |triangle| {
+        distance_to_triangle(center, triangle) <= radius
+    }))
+}
+
+/// Returns every triangle and copy that overlaps the polygon with vertices `coords`, a flat list of
+/// `[x1, y1, x2, y2, ...]` pairs.
+#[wasm_bindgen]
+pub fn triangles_in_polygon(coords: &[f32]) -> Result<Vec<QueriedTriangle>, String> {
+    let polygon =
+        build_polygon_mask(coords).ok_or("Query polygon must have at least 3 vertices")?;
+    Ok(get_queried_triangles(query_triangles(
+        &polygon.bounds,
+        |triangle| polygon.intersects_triangle(triangle),
+    )))
+}
+
+/// Returns the `k` triangles and copies closest to `(x, y)`, closest first.  Triangles containing
+/// the point have a distance of 0.
+#[wasm_bindgen]
+pub fn nearest_triangles(x: f32, y: f32, k: usize) -> Vec<QueriedTriangle> {
+    let mut results = Vec::new();
+    world().visit(&mut NearestTrianglesVisitor {
+        pt: p2(x, y),
+        k,
+        results: &mut results,
+    });
+    get_queried_triangles(results.into_iter().map(|(_, leaf)| leaf))
+}
+
+/// Restricts triangles to the region filled by the SVG path data `d`, after scaling its coordinates
+/// by `scale` and offsetting them by `(x, y)`.  Existing triangles outside of the region are
+/// removed.
+#[wasm_bindgen]
+pub fn set_allowed_path(
+    d: &str,
+    fill_rule: FillRule,
+    x: f32,
+    y: f32,
+    scale: f32,
+) -> Result<(), String> {
+    let region = Mask::from_svg_path(d, fill_rule, Vector2::new(x, y), scale)?;
+    if region.polygons.is_empty() {
+        return Err("Allowed path doesn't enclose any area".into());
+    }
+    set_allowed_region(region);
+    Ok(())
+}
+
+/// Restricts triangles to the glyphs of `text` set in the TrueType/OpenType font `font_bytes`, laid
+/// out as large as possible inside of the rectangle at `(x, y)` with size `width` by `height`.
+/// Lines are separated by `\n`.  Existing triangles outside of the glyphs are removed.
+#[wasm_bindgen]
+pub fn set_allowed_text(
+    text: &str,
+    font_bytes: &[u8],
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) -> Result<(), String> {
+    let layout = AABB::new(p2(x, y), p2(x + width, y + height));
+    set_allowed_region(text::build_text_mask(text, font_bytes, &layout)?);
+    Ok(())
+}
+
+fn set_allowed_region(region: Mask) {
+    field_conf().allowed_region = Some(region.clone());
+    prune_triangles(|triangle| !region.contains_triangle(triangle));
+}
+
+/// Allows triangles to grow anywhere on the canvas again.
+#[wasm_bindgen]
+pub fn clear_allowed_path() {
+    field_conf().allowed_region = None;
+}
+
+/// Keeps triangles out of the region filled by the SVG path data `d`, after scaling its
+/// coordinates by `scale` and offsetting them by `(x, y)`, and removes any existing triangles that
+/// overlap it.  Returns an id that can be used to remove the region.
+#[wasm_bindgen]
+pub fn add_forbidden_path(
+    d: &str,
+    fill_rule: FillRule,
+    x: f32,
+    y: f32,
+    scale: f32,
+) -> Result<usize, String> {
+    let region = Mask::from_svg_path(d, fill_rule, Vector2::new(x, y), scale)?;
+    if region.polygons.is_empty() {
+        return Err("Forbidden path doesn't enclose any area".into());
+    }
+    Ok(add_forbidden_region(region))
+}
+
+/// Keeps triangles out of the glyphs of `text` set in the TrueType/OpenType font `font_bytes`, laid
+/// out as large as possible inside of the rectangle at `(x, y)` with size `width` by `height`, and
+/// removes any existing triangles that overlap them.  Returns an id that can be passed to
+/// `remove_forbidden_path` to remove the region.
+#[wasm_bindgen]
+pub fn add_forbidden_text(
+    text: &str,
+    font_bytes: &[u8],
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) -> Result<usize, String> {
+    let layout = AABB::new(p2(x, y), p2(x + width, y + height));
+    Ok(add_forbidden_region(text::build_text_mask(
+        text, font_bytes, &layout,
+    )?))
+}
+
+fn add_forbidden_region(region: Mask) -> usize {
+    let forbidden_regions = &mut field_conf().forbidden_regions;
+    forbidden_regions.push(Some(region.clone()));
+    let region_id = forbidden_regions.len() - 1;
+    prune_triangles(|triangle| region.intersects_triangle(triangle));
+    region_id
+}
+
+/// Removes the forbidden region with id `region_id`.  Returns `false` if there was no forbidden
+/// region with that id.
+#[wasm_bindgen]
+pub fn remove_forbidden_path(region_id: usize) -> bool {
+    field_conf()
+        .forbidden_regions
+        .get_mut(region_id)
+        .and_then(Option::take)
+        .is_some()
+}
+
+/// Removes all forbidden regions.  Their ids aren't handed out again.
+#[wasm_bindgen]
+pub fn clear_forbidden_paths() {
+    field_conf()
+        .forbidden_regions
+        .iter_mut()
+        .for_each(|region| *region = None);
+}
+
+/// Enables flow field steering for the chain with index `chain_ix`.  Chains sharing the same seed
+/// and settings follow the same field.
+#[wasm_bindgen]
+pub fn set_flow_field(chain_ix: usize, scale: f32, strength: f32, time_scale: f32) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.flow_field = Some(FlowFieldConf {
+        noise: PerlinNoise::new(env.conf.prng_seed.to_bits()),
+        scale,
+        strength: strength.clamp(0., 1.),
+        time_scale,
+    });
+}
+
+/// Disables flow field steering for the chain with index `chain_ix`.
+#[wasm_bindgen]
+pub fn clear_flow_field(chain_ix: usize) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.conf.flow_field = None;
+}
+
+#[inline]
+const fn deg_to_rad(degrees: f32) -> f32 {
+    degrees * (f32::consts::PI / 180.0)
+}
+
+/// Wraps an angle in radians into the range `[-PI, PI]`
+#[inline]
+fn wrap_angle(angle: f32) -> f32 {
+    let wrapped = (angle + f32::consts::PI).rem_euclid(f32::consts::TAU);
+    wrapped - f32::consts::PI
+}
+
+/// Rotates `rotation` so that the direction in which a triangle with that rotation grows is pulled
+/// toward `heading` by `strength`, where 0 leaves it unchanged and 1 points it directly at `heading`.
+///
+/// Triangles are built with their free vertices in the +y direction from their origin, so they grow
+/// in the direction `rotation + PI / 2`.
+#[inline]
+fn steer_toward(rotation: f32, heading: f32, strength: f32) -> f32 {
+    let growth_direction = rotation + f32::consts::FRAC_PI_2;
+    rotation + wrap_angle(heading - growth_direction) * strength
+}
+
+#[inline]
+fn min3(a: f32, b: f32, c: f32) -> f32 {
+    a.min(b).min(c)
+}
+
+#[inline]
+fn max3(a: f32, b: f32, c: f32) -> f32 {
+    a.max(b).max(c)
+}
+
+#[inline]
+fn bounds(v1: Point2<f32>, v2: Point2<f32>, v3: Point2<f32>) -> (Point2<f32>, Point2<f32>) {
+    (
+        p2(min3(v1.x, v2.x, v3.x), min3(v1.y, v2.y, v3.y)),
+        p2(max3(v1.x, v2.x, v3.x), max3(v1.y, v2.y, v3.y)),
+    )
+}
+
+#[inline]
+fn centroid(triangle: &TriangleBuf) -> Point2<f32> {
+    p2(
+        (triangle[0].x + triangle[1].x + triangle[2].x) / 3.,
+        (triangle[0].y + triangle[1].y + triangle[2].y) / 3.,
+    )
+}
+
+/// Returns the mean length of `triangle`'s sides
+fn get_triangle_size(triangle: &TriangleBuf) -> f32 {
+    ((triangle[0] - triangle[1]).norm()
+        + (triangle[1] - triangle[2]).norm()
+        + (triangle[2] - triangle[0]).norm())
+        / 3.
+}
+
+/// Returns the distance from `pt` to the line segment between `a` and `b`
+fn distance_to_segment(pt: Point2<f32>, a: Point2<f32>, b: Point2<f32>) -> f32 {
+    let ab = b - a;
+    let len_squared = ab.norm_squared();
+    if len_squared == 0. {
+        return (pt - a).norm();
+    }
+
+    let t = ((pt - a).dot(&ab) / len_squared).clamp(0., 1.);
+    (pt - (a + ab * t)).norm()
+}
+
+fn point_in_triangle(pt: Point2<f32>, triangle: &TriangleBuf) -> bool {
+    let sign =
+        |a: Point2<f32>, b: Point2<f32>| (pt.x - b.x) * (a.y - b.y) - (a.x - b.x) * (pt.y - b.y);
+    let d1 = sign(triangle[0], triangle[1]);
+    let d2 = sign(triangle[1], triangle[2]);
+    let d3 = sign(triangle[2], triangle[0]);
+    let has_neg = d1 < 0. || d2 < 0. || d3 < 0.;
+    let has_pos = d1 > 0. || d2 > 0. || d3 > 0.;
+    !(has_neg && has_pos)
+}
+
+/// Returns the distance from `pt` to the closest point of `triangle`, which is 0 if `pt` is inside
+/// of it.
+fn distance_to_triangle(pt: Point2<f32>, triangle: &TriangleBuf) -> f32 {
+    if point_in_triangle(pt, triangle) {
+        return 0.;
+    }
+
+    min3(
+        distance_to_segment(pt, triangle[0], triangle[1]),
+        distance_to_segment(pt, triangle[1], triangle[2]),
+        distance_to_segment(pt, triangle[2], triangle[0]),
+    )
+}
+
+/// Returns the distance between the segment from `a` to `b` and the closest point of `triangle`,
+/// which is 0 if they intersect
+fn distance_segment_to_triangle(a: Point2<f32>, b: Point2<f32>, triangle: &TriangleBuf) -> f32 {
+    let edges = [
+        (triangle[0], triangle[1]),
+        (triangle[1], triangle[2]),
+        (triangle[2], triangle[0]),
+    ];
+    if point_in_triangle(a, triangle)
+        || edges
+            .iter()
+            .any(|&(p1, p2)| check_line_seg_intersection(a, b, p1, p2))
+    {
+        return 0.;
+    }
+
+    let vertex_distance = triangle
+        .iter()
+        .map(|&pt| distance_to_segment(pt, a, b))
+        .fold(f32::INFINITY, f32::min);
+    vertex_distance.min(distance_to_triangle(a, triangle).min(distance_to_triangle(b, triangle)))
+}
+
+#[inline]
+fn ccw(p1: Point2<f32>, p2: Point2<f32>, p3: Point2<f32>) -> bool {
+    (p3.y - p1.y) * (p2.x - p1.x) >= (p2.y - p1.y) * (p3.x - p1.x)
+}
+
+/// adapted from https://stackoverflow.com/a/9997374/3833068
+#[inline]
+fn check_line_seg_intersection(
+    l1p1: Point2<f32>,
+    l1p2: Point2<f32>,
+    l2p1: Point2<f32>,
+    l2p2: Point2<f32>,
+) -> bool {
+    ccw(l1p1, l2p1, l2p2) != ccw(l1p2, l2p1, l2p2) && ccw(l1p1, l1p2, l2p1) != ccw(l1p1, l1p2, l2p2)
+}
+
+/// Returns `true` if the interiors of the two triangles overlap.  Two triangles don't overlap if
+/// and only if some side of one of them has all of the other on or beyond it, so triangles that
+/// only share a vertex or a side never collide, whichever way they are wound.
+fn check_triangle_collision(t1: &TriangleBuf, t2: &TriangleBuf) -> bool {
+    !has_separating_side(t1, t2) && !has_separating_side(t2, t1)
+}
+
+/// Returns `true` if `other` lies entirely on or outside of one of the sides of `triangle`
+fn has_separating_side(triangle: &TriangleBuf, other: &TriangleBuf) -> bool {
+    let cross = |a: Vector2<f32>, b: Vector2<f32>| a.x * b.y - a.y * b.x;
+    let winding = cross(triangle[1] - triangle[0], triangle[2] - triangle[0]).signum();
+    (0..3).any(|i| {
+        let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+        other.iter().all(|&pt| cross(b - a, pt - a) * winding <= 0.)
+    })
+}
+
+/// Returns `true` if `triangle` overlaps the axis-aligned rectangle `rect`
+fn check_rect_collision(triangle: &TriangleBuf, rect: &AABB<f32>) -> bool {
+    if !get_triangle_bv(triangle).intersects(rect) {
+        return false;
+    }
+
+    let (mins, maxs) = (rect.mins, rect.maxs);
+    let pt_within_rect =
+        |pt: &Point2<f32>| pt.x >= mins.x && pt.x <= maxs.x && pt.y >= mins.y && pt.y <= maxs.y;
+    if triangle.iter().any(pt_within_rect) {
+        return true;
+    }
+    // The rectangle may be entirely inside of the triangle, or the two may cross without either
+    // containing a vertex of the other
+    let corners = [mins, p2(maxs.x, mins.y), maxs, p2(mins.x, maxs.y)];
+    if corners
+        .iter()
+        .any(|&corner| point_in_triangle(corner, triangle))
+    {
+        return true;
+    }
+    check_triangle_collision(triangle, &[corners[0], corners[1], corners[2]])
+        || check_triangle_collision(triangle, &[corners[2], corners[3], corners[0]])
+}
+
+struct TriangleCollisionVisitor<'a> {
+    pub triangle: &'a TriangleBuf,
+    pub triangle_bv: &'a AABB<f32>,
+    // pub triangles: &'a [Vec<TriangleHandle>; CHAIN_COUNT],
+    pub does_collide: &'a mut bool,
+}
+
+impl<'a> Visitor<(usize, usize, usize), AABB<f32>> for TriangleCollisionVisitor<'a> {
+    fn visit(&mut self, bv: &AABB<f32>, data: Option<&(usize, usize, usize)>) -> VisitStatus {
+        if let Some(&(chain_ix, triangle_ix, copy_ix)) = data {
+            // We reached a leaf node, so we check to see if our candidate triangle collides with it
+            if check_triangle_collision(
+                self.triangle,
+                get_triangle(chain_ix, triangle_ix).get_geometry(copy_ix),
+            ) {
+                *self.does_collide = true;
+                VisitStatus::ExitEarly
+            } else {
+                VisitStatus::Stop
+            }
+        } else if self.triangle_bv.intersects(bv) {
+            // The BV of our candidate triangle collides with the BV of this level of the BVH
+            VisitStatus::Continue
+        } else {
+            // Nothing collides, so we bail out of this branch of the tree
+            VisitStatus::Stop
+        }
+    }
+}
+
+/// Returns `true` if `triangle` intersects any triangle in the collision world
+fn check_world_collision(triangle: &TriangleBuf, triangle_bv: &AABB<f32>) -> bool {
+    let mut does_collide = false;
+    let mut visitor = TriangleCollisionVisitor {
+        triangle,
+        triangle_bv,
+        // triangles: unsafe { &*TRIANGLES },
+        does_collide: &mut does_collide,
+    };
+    world().visit(&mut visitor);
+    does_collide
+}
+
+/// Collects the `(chain_ix, triangle_ix, copy_ix)` of every triangle whose bounding volume
+/// intersects `aabb`
+struct AABBQueryVisitor<'a> {
+    pub aabb: &'a AABB<f32>,
+    pub results: &'a mut Vec<(usize, usize, usize)>,
+}
+
+impl<'a> Visitor<(usize, usize, usize), AABB<f32>> for AABBQueryVisitor<'a> {
+    fn visit(&mut self, bv: &AABB<f32>, data: Option<&(usize, usize, usize)>) -> VisitStatus {
+        if !self.aabb.intersects(bv) {
+            return VisitStatus::Stop;
+        }
+
+        if let Some(&leaf) = data {
+            self.results.push(leaf);
+        }
+        VisitStatus::Continue
+    }
+}
+
+/// Returns the `(chain_ix, triangle_ix, copy_ix)` of every triangle whose bounding volume
+/// intersects `aabb` and for which `is_match` returns `true`, ordered by chain, slot, and copy
+fn query_triangles(
+    aabb: &AABB<f32>,
+    is_match: impl Fn(&TriangleBuf) -> bool,
+) -> Vec<(usize, usize, usize)> {
+    let mut candidates = Vec::new();
+    world().visit(&mut AABBQueryVisitor {
+        aabb,
+        results: &mut candidates,
+    });
+    candidates.retain(|&(chain_ix, triangle_ix, copy_ix)| {
+        is_match(get_triangle(chain_ix, triangle_ix).get_geometry(copy_ix))
+    });
+    candidates.sort_unstable();
+    candidates
+}
+
+/// Returns the `(chain_ix, triangle_ix, copy_ix)` of a triangle containing `pt`
+fn find_triangle_at(pt: Point2<f32>) -> Option<(usize, usize, usize)> {
+    query_triangles(&AABB::new(pt, pt), |triangle| {
+        point_in_triangle(pt, triangle)
+    })
+    .into_iter()
+    .next()
+}
+
+/// Collects the `(distance, (chain_ix, triangle_ix, copy_ix))` of the `k` triangles closest to
+/// `pt`, sorted by distance.  Branches of the tree further away than the furthest triangle found so
+/// far are skipped once `k` triangles have been found.
+struct NearestTrianglesVisitor<'a> {
+    pub pt: Point2<f32>,
+    pub k: usize,
+    pub results: &'a mut Vec<(f32, (usize, usize, usize))>,
+}
+
+impl<'a> Visitor<(usize, usize, usize), AABB<f32>> for NearestTrianglesVisitor<'a> {
+    fn visit(&mut self, bv: &AABB<f32>, data: Option<&(usize, usize, usize)>) -> VisitStatus {
+        let (mins, maxs) = (bv.mins, bv.maxs);
+        let bv_distance = Vector2::new(
+            (mins.x - self.pt.x).max(self.pt.x - maxs.x).max(0.),
+            (mins.y - self.pt.y).max(self.pt.y - maxs.y).max(0.),
+        )
+        .norm();
+        let is_full = self.results.len() >= self.k;
+        if self.k == 0 || is_full && bv_distance >= self.results[self.k - 1].0 {
+            return VisitStatus::Stop;
+        }
+
+        if let Some(&(chain_ix, triangle_ix, copy_ix)) = data {
+            let geometry = get_triangle(chain_ix, triangle_ix).get_geometry(copy_ix);
+            let distance = distance_to_triangle(self.pt, geometry);
+            let insert_ix = self
+                .results
+                .partition_point(|&(other_distance, _)| other_distance <= distance);
+            if insert_ix < self.k {
+                self.results
+                    .insert(insert_ix, (distance, (chain_ix, triangle_ix, copy_ix)));
+                self.results.truncate(self.k);
+            }
+        }
+        VisitStatus::Continue
+    }
+}
+
+/// Attempts to find a valid rotation for the next triangle, returning the proposed triangle if it
+/// is found.
+fn find_triangle_placement(
+    env: &Env,
+    origin: Point2<f32>,
+    rotation: f32,
+    size: f32,
+) -> Option<(AABB<f32>, TriangleBuf)> {
+    let max_rotation_rads = env.conf.max_rotation_rads;
+    let mut proposed_rotation =
+        rotation + rng().gen_range(-max_rotation_rads, max_rotation_rads + 0.00001);
+    if let Some(flow_field) = &env.conf.flow_field {
+        let heading = flow_field.get_heading(origin, env.generation);
+        proposed_rotation = steer_toward(proposed_rotation, heading, flow_field.strength);
+    }
+    if let Some((heading, strength)) = env.steering {
+        proposed_rotation = steer_toward(proposed_rotation, heading, strength);
+    }
+    // determine if this proposed triangle would intersect any other triangle
+    let proposed_triangle = env.build_triangle(origin, proposed_rotation, size);
+    let bounding_box = check_placement(env, &proposed_triangle)?;
+
+    // we've found a valid triangle placement
+    Some((bounding_box, proposed_triangle))
+}
+
+/// Checks that `triangle` and all of its symmetric copies lie within the canvas without colliding
+/// with any existing triangles or each other and that `triangle` lies within the chain's territory,
+/// returning its bounding volume if so.
+fn check_placement(env: &Env, triangle: &TriangleBuf) -> Option<AABB<f32>> {
+    if !field_conf().is_triangle_allowed(triangle, &env.conf)
+        || !env.conf.is_in_territory(triangle)
+        || !field_conf().roll_density(triangle)
+    {
+        return None;
+    }
+    check_placement_collisions(env, triangle)
+}
+
+/// Checks that `triangle` and all of its symmetric copies don't collide with any existing triangles
+/// or each other and that the copies lie within the canvas, returning the triangle's bounding
+/// volume if so.
+fn check_placement_collisions(env: &Env, triangle: &TriangleBuf) -> Option<AABB<f32>> {
+    let bounding_box = get_triangle_bv(triangle);
+    if check_world_collision(triangle, &bounding_box) {
+        return None;
+    }
+
+    // all symmetric copies of the triangle need to be valid placements as well, without colliding
+    // with the original or each other
+    let copies = field_conf().get_copies(triangle, &env.conf);
+    for (i, copy) in copies.iter().enumerate() {
+        if !field_conf().is_triangle_allowed(copy, &env.conf)
+            || check_triangle_collision(copy, triangle)
+            || copies[..i]
+                .iter()
+                .any(|other| check_triangle_collision(copy, other))
+            || check_world_collision(copy, &get_triangle_bv(copy))
+        {
+            return None;
+        }
+    }
+
+    Some(bounding_box)
+}
+
+/// Releases a walker triangle from a random point on the edge of the canvas and moves it randomly,
+/// tumbling by up to `max_rotation_rads` with each step, until it comes within `stick_distance` of
+/// one of the chain's triangles that can accept a new child.  Near the chain, steps that would make
+/// the walker overlap any triangle are rejected.  The walker sticks where it is if it passes the
+/// same placement checks as any other triangle, and the touched triangle is set as its parent.
+fn generate_dla_triangle(
+    env: &mut Env,
+    step_size: f32,
+    stick_distance: f32,
+    max_walk_steps: usize,
+) -> Option<(AABB<f32>, TriangleBuf, f32)> {
+    let (width, height) = (env.conf.canvas_width as f32, env.conf.canvas_height as f32);
+    let mut origin = match rng().gen_range(0, 4) {
+        0 => p2(rng().gen_range(0., width), 0.),
+        1 => p2(rng().gen_range(0., width), height),
+        2 => p2(0., rng().gen_range(0., height)),
+        _ => p2(width, rng().gen_range(0., height)),
+    };
+    let mut rotation = rng().gen_range(0., f32::consts::TAU);
+
+    // Walkers take large steps while they're far from the chain so that they don't spend
+    // thousands of steps wandering around empty space
+    let mut chain_bounds: Option<AABB<f32>> = None;
+    for handle in env.triangles().iter().filter(|handle| !handle.removed) {
+        let bv = get_triangle_bv(&handle.geometry);
+        chain_bounds = Some(match chain_bounds {
+            Some(chain_bounds) => chain_bounds.merged(&bv),
+            None => bv,
+        });
+    }
+    let chain_bounds = chain_bounds?.loosened(stick_distance + env.conf.triangle_size);
+
+    let max_rotation_rads = env.conf.max_rotation_rads;
+    for _ in 0..max_walk_steps {
+        let distance_to_chain =
+            chain_bounds.distance_to_point(&Isometry2::identity(), &origin, true);
+        let step = step_size.max(distance_to_chain * 0.5);
+        let direction = rng().gen_range(0., f32::consts::TAU);
+        let next_origin = p2(
+            (origin.x + direction.cos() * step).clamp(0., width),
+            (origin.y + direction.sin() * step).clamp(0., height),
+        );
+        let next_rotation =
+            rotation + rng().gen_range(-max_rotation_rads, max_rotation_rads + 0.00001);
+        if distance_to_chain > 0. {
+            origin = next_origin;
+            rotation = next_rotation;
+            continue;
+        }
+
+        let walker = env.build_triangle(next_origin, next_rotation, env.conf.triangle_size);
+        let walker_bv = get_triangle_bv(&walker);
+        if check_world_collision(&walker, &walker_bv) {
+            continue;
+        }
+        origin = next_origin;
+        rotation = next_rotation;
+
+        // stick to the closest triangle of the chain within reach
+        let mut closest: Option<(usize, f32)> = None;
+        for (chain_ix, triangle_ix, copy_ix) in
+            query_triangles(&walker_bv.loosened(stick_distance), |_| true)
+        {
+            if chain_ix != env.chain_ix || copy_ix != 0 {
+                continue;
+            }
+            let handle = env.get_triangle(triangle_ix);
+            if !handle.has_free_child_slot() {
+                continue;
+            }
+
+            let distance = (0..3)
+                .map(|i| {
+                    distance_segment_to_triangle(walker[i], walker[(i + 1) % 3], &handle.geometry)
+                })
+                .fold(f32::INFINITY, f32::min);
+            if distance <= stick_distance && closest.is_none_or(|(_, closest)| distance < closest) {
+                closest = Some((triangle_ix, distance));
+            }
+        }
+        let parent_ix = match closest {
+            Some((parent_ix, _)) => parent_ix,
+            None => continue,
+        };
+
+        env.last_triangle = env.get_triangle(parent_ix).geometry;
+        env.last_triangle_ix = parent_ix;
+        // the walker is scaled around its tip to the size of the parent's children, which keeps it
+        // inside of the spot that the walker was found to fit in if it shrinks
+        let size = match env.get_child_triangle_size() {
+            Some(size) => size * field_conf().get_size_scale(origin),
+            None => continue,
+        };
+        let triangle = env.build_triangle(origin, rotation, size);
+        if let Some(bv) = check_placement(env, &triangle) {
+            return Some((bv, triangle, size));
+        }
+    }
+
+    None
+}
+
+/// Places the next tile of a Penrose tiling against one of the open edges of the last triangle, or
+/// seeds a new tiling at the last triangle's apex if the chain has no triangles to grow from.
+fn generate_penrose_triangle(env: &mut Env) -> Option<(AABB<f32>, TriangleBuf, f32)> {
+    let leg_length = env.conf.triangle_size;
+    if env.last_triangle_ix == usize::MAX {
+        let rotation = rng().gen_range(0., f32::consts::TAU);
+        let seed = penrose::build_seed_triangle(env.last_triangle[0], rotation, leg_length);
+        let bv = check_placement(env, &seed)?;
+        return Some((bv, seed, leg_length));
+    }
+    if !env.get_triangle(env.last_triangle_ix).has_free_child_slot() {
+        return None;
+    }
+
+    let mut candidates: Vec<TriangleBuf> = penrose::EDGES
+        .iter()
+        .flat_map(|&edge| penrose::get_matching_neighbors(&env.last_triangle, edge, leg_length))
+        .map(|(triangle, _)| field_conf().wrap_triangle(triangle, &env.conf))
+        .collect();
+    candidates.shuffle(rng());
+
+    let epsilon = leg_length * PENROSE_VERTEX_EPSILON;
+    for mut candidate in candidates {
+        // Tiles meet vertex to vertex, so vertices landing within rounding error of an existing
+        // tile's vertex are snapped onto it to make the tiles share it exactly
+        let neighbors = query_triangles(&get_triangle_bv(&candidate).loosened(epsilon), |_| true);
+        for pt in candidate.iter_mut() {
+            let snapped = neighbors
+                .iter()
+                .flat_map(|&(chain_ix, triangle_ix, copy_ix)| {
+                    get_triangle(chain_ix, triangle_ix).get_geometry(copy_ix)
+                })
+                .find(|other| (*other - *pt).norm() < epsilon);
+            if let Some(&other) = snapped {
+                *pt = other;
+            }
+        }
+        let bv = match check_placement(env, &candidate) {
+            Some(bv) => bv,
+            None => continue,
+        };
+
+        // every edge that the candidate shares with the chain has to satisfy the matching rules
+        let is_valid = neighbors.iter().all(|&(chain_ix, triangle_ix, copy_ix)| {
+            let other = get_triangle(chain_ix, triangle_ix).get_geometry(copy_ix);
+            chain_ix != env.chain_ix
+                || copy_ix != 0
+                || penrose::check_shared_edge(&candidate, other, epsilon) != Some(false)
+        });
+        if is_valid {
+            return Some((bv, candidate, leg_length));
+        }
+    }
+
+    None
+}
+
+fn generate_triangle(env: &mut Env) -> Option<(AABB<f32>, TriangleBuf, f32)> {
+    if let GrowthMode::Penrose = env.conf.growth_mode {
+        return generate_penrose_triangle(env);
+    }
+    if let GrowthMode::DiffusionLimitedAggregation {
+        step_size,
+        stick_distance,
+        max_walk_steps,
+    } = env.conf.growth_mode
+    {
+        // The chain needs an initial triangle to aggregate around, which is placed normally
+        if env.live_triangle_count() != 0 {
+            return generate_dla_triangle(env, step_size, stick_distance, max_walk_steps);
+        }
+    }
+
+    // the last triangle may have had its child slots filled since it was selected, for example by
+    // being subdivided
+    if env.last_triangle_ix != usize::MAX
+        && !env.get_triangle(env.last_triangle_ix).has_free_child_slot()
+    {
+        return None;
+    }
+    let size = env.get_child_triangle_size()?;
+
+    // pick one of the other two vertices to use as the new origin, preferring the one closest to
+    // the direction we're steering toward if there is one
+    let use_first_vertex = match env.steering {
+        Some((heading, _)) => {
+            let center = centroid(&env.last_triangle);
+            let angle_to = |pt: Point2<f32>| {
+                let offset = pt - center;
+                wrap_angle(offset.y.atan2(offset.x) - heading).abs()
+            };
+            angle_to(env.last_triangle[1]) <= angle_to(env.last_triangle[2])
+        }
+        None => rng().gen_range(0, 2) == 0,
+    };
+    let (ix, rot_offset) = if use_first_vertex {
+        (1, deg_to_rad(env.conf.rotation_offset))
+    } else {
+        (2, deg_to_rad(-env.conf.rotation_offset))
+    };
+
+    let origin = env.last_triangle[ix];
+    let size = size * field_conf().get_size_scale(origin);
+    for _ in 0..PLACEMENT_ATTEMPTS {
+        let placement_opt = find_triangle_placement(env, origin, env.rotation + rot_offset, size);
+        if let Some((bv, triangle)) = placement_opt {
+            env.rotation += rot_offset;
+            return Some((bv, triangle, size));
+        }
+    }
+
+    if env.conf.gap_fill.is_some() {
+        env.failed_origins.push(origin);
+    }
+    None // failed to place a triangle at this origin in `PLACEMENT_ATTTEMPTS` attempts
+}
+
+/// Retries the most recent origins where placement failed with progressively smaller triangles.
+/// A triangle that fits is attached to the nearest triangle of the chain that it shares a vertex
+/// with and that has a free child slot, and is rejected if there is no such triangle.
+fn fill_gap(env: &mut Env, gap_fill: &GapFillConf) -> Option<(AABB<f32>, TriangleBuf, f32)> {
+    let mut origins: Vec<Point2<f32>> = Vec::with_capacity(GAP_FILL_MAX_REGIONS);
+    for origin in env.failed_origins.iter().rev() {
+        if origins.len() == GAP_FILL_MAX_REGIONS {
+            break;
+        }
+        if !origins.contains(origin) {
+            origins.push(*origin);
+        }
+    }
+
+    for origin in origins {
+        let mut size = env.conf.triangle_size * gap_fill.shrink_factor;
+        while size >= gap_fill.min_triangle_size {
+            for _ in 0..PLACEMENT_ATTEMPTS {
+                // gaps can have any orientation, so we don't try to continue the chain's rotation
+                let rotation = rng().gen_range(0., f32::consts::TAU);
+                let (bv, triangle) = match find_triangle_placement(env, origin, rotation, size) {
+                    Some(placement) => placement,
+                    None => continue,
+                };
+
+                let center = centroid(&triangle);
+                let parent =
+                    query_triangles(&bv, |other| other.iter().any(|pt| triangle.contains(pt)))
+                        .into_iter()
+                        .filter(|&(chain_ix, ix, _)| {
+                            chain_ix == env.chain_ix
+                                && ix != env.oldest_triangle_ix
+                                && env.get_triangle(ix).has_free_child_slot()
+                        })
+                        .min_by(|&(_, a_ix, a_copy_ix), &(_, b_ix, b_copy_ix)| {
+                            let distance = |ix: usize, copy_ix: usize| {
+                                (centroid(env.get_triangle(ix).get_geometry(copy_ix)) - center)
+                                    .norm()
+                            };
+                            distance(a_ix, a_copy_ix).total_cmp(&distance(b_ix, b_copy_ix))
+                        });
+                if let Some((_, parent_ix, _)) = parent {
+                    env.last_triangle = env.get_triangle(parent_ix).geometry;
+                    env.last_triangle_ix = parent_ix;
+                    return Some((bv, triangle, size));
+                }
+            }
+            size *= gap_fill.shrink_factor;
+        }
+    }
+
+    None
+}
+
+fn place_triangle(env: &mut Env, insert_at_oldest_ix: bool) -> Option<()> {
+    env.failed_origins.clear();
+    if env.dormant {
+        env.reseed();
+    }
+    for _ in 0..PLACEMENT_BAILOUT_THRESHOLD {
+        if env.dormant {
+            return None;
+        }
+        if let Some((bv, triangle, size)) = generate_triangle(env) {
+            insert_triangle(env, insert_at_oldest_ix, bv, triangle, size);
+            return Some(());
+        }
+
+        // we failed to place a triangle at this origin; we have to pick a new origin point.  If we
+        // were steering toward attractors, that branch may be blocked so we fall back to a random
+        // one rather than picking it again.
+        if env.steering.is_some() {
+            env.set_random_last_triangle();
+        } else {
+            env.set_new_last_triangle();
+        }
+    }
+
+    // We've run out of room for full-sized triangles, so try to pack smaller ones into the gaps
+    let gap_fill = env.conf.gap_fill.clone()?;
+    let (bv, triangle, size) = fill_gap(env, &gap_fill)?;
+    insert_triangle(env, insert_at_oldest_ix, bv, triangle, size);
+    Some(())
+}
+
+/// Returns the `(fill, border)` colors for a triangle of the chain, sampled from the chain's source
+/// image if it has one
+fn get_triangle_colors(env: &Env, triangle: &TriangleBuf) -> (String, String) {
+    env.conf
+        .image_colors
+        .as_ref()
+        .and_then(|image_colors| image_colors.get_colors(triangle))
+        .unwrap_or_else(|| {
+            (
+                env.conf.triangle_color.clone(),
+                env.conf.triangle_border_color.clone(),
+            )
+        })
+}
+
+/// Renders a triangle along with its symmetric and wrapped copies and adds them to the collision
+/// world under the slot `triangle_ix`, returning a handle for it that isn't linked to any other
+/// triangles.
+fn build_triangle_handle(
+    env: &Env,
+    triangle_ix: usize,
+    bv: AABB<f32>,
+    triangle: TriangleBuf,
+    size: f32,
+    depth: usize,
+) -> TriangleHandle {
+    let (color, border_color) = get_triangle_colors(env, &triangle);
+    let dom_id = render_triangle_array(&triangle, &color, &border_color);
+    let leaf_id = world().insert(DBVTLeaf::new(bv, (env.chain_ix, triangle_ix, 0)));
+    let stamp = field_conf().next_triangle_stamp;
+    field_conf().next_triangle_stamp += 1;
+    let copies = field_conf()
+        .get_copies(&triangle, &env.conf)
+        .into_iter()
+        .enumerate()
+        .map(|(i, geometry)| {
+            let (color, border_color) = get_triangle_colors(env, &geometry);
+            TriangleCopy {
+                dom_id: render_triangle_array(&geometry, &color, &border_color),
+                collider_handle: world().insert(DBVTLeaf::new(
+                    get_triangle_bv(&geometry),
+                    (env.chain_ix, triangle_ix, i + 1),
+                )),
+                geometry,
+                color,
+                border_color,
+            }
+        })
+        .collect();
+
+    TriangleHandle {
+        dom_id,
+        color,
+        border_color,
+        collider_handle: leaf_id,
+        geometry: triangle,
+        prev_node: None,
+        next_node_1: None,
+        next_node_2: None,
+        size,
+        depth,
+        generation: env.generation,
+        stamp,
+        copies,
+        removed: false,
+    }
+}
+
+/// Stores `handle` in the slot `triangle_ix` of the chain, which may be one past the last slot.
+fn store_triangle_handle(env: &Env, triangle_ix: usize, handle: TriangleHandle) {
+    if triangle_ix == env.triangles().len() {
+        env.triangles_mut().push(handle);
+    } else {
+        *env.get_triangle_mut(triangle_ix) = handle;
+    }
+}
+
+/// Links the triangle at `child_ix` as a child of the triangle at `parent_ix`.
+fn link_child(env: &Env, parent_ix: usize, child_ix: usize) {
+    let parent = env.get_triangle_mut(parent_ix);
+    match (parent.next_node_1, parent.next_node_2) {
+        (Some(_), None) => {
+            parent.next_node_2 = Some(child_ix);
+            debug_assert!(parent.next_node_2 != parent.prev_node);
+            debug_assert!(parent.next_node_2 != parent.next_node_1);
+        }
+        (None, Some(_)) | (None, None) => {
+            parent.next_node_1 = Some(child_ix);
+            debug_assert!(parent.next_node_1 != parent.prev_node);
+            debug_assert!(parent.next_node_1 != parent.next_node_2);
+        }
+        (Some(_), Some(_)) => {
+            panic!("Tried to add new triangle to triangle with two children");
+        }
+    }
+    env.get_triangle_mut(child_ix).prev_node = Some(parent_ix);
+}
+
+/// Renders a triangle and adds it to the collision world and the chain, linking it as a child of
+/// the chain's last triangle.
+fn insert_triangle(
+    env: &mut Env,
+    insert_at_oldest_ix: bool,
+    bv: AABB<f32>,
+    triangle: TriangleBuf,
+    size: f32,
+) {
+    let insertion_ix = if insert_at_oldest_ix {
+        env.oldest_triangle_ix
+    } else {
+        env.triangles().len()
+    };
+    let depth = if env.last_triangle_ix == usize::MAX {
+        0
+    } else {
+        env.get_triangle(env.last_triangle_ix).depth + 1
+    };
+
+    let handle = build_triangle_handle(env, insertion_ix, bv, triangle, size, depth);
+    store_triangle_handle(env, insertion_ix, handle);
+    if env.last_triangle_ix != usize::MAX {
+        link_child(env, env.last_triangle_ix, insertion_ix);
+    }
+    env.last_triangle = triangle;
+    env.last_triangle_ix = insertion_ix;
+
+    if let GrowthMode::SpaceColonization { kill_radius, .. } = env.conf.growth_mode {
+        env.consume_attractors(&triangle, kill_radius);
+        // pick the next branch point based on the attractors that are left
+        env.set_new_last_triangle();
+    }
+    // keep heading for the target from the new tip, which it may have moved away from
+    if let GrowthMode::SeekTarget {
+        steering_strength, ..
+    } = env.conf.growth_mode
+    {
+        env.steer_toward_target(steering_strength);
+    }
+}
+
+impl SubdivisionPattern {
+    /// Returns the index of the child that each child is linked beneath when subdividing.  The
+    /// first child takes the place of the subdivided triangle.
+    fn get_link_parents(self) -> &'static [usize] {
+        match self {
+            SubdivisionPattern::Midpoint => &[0, 0, 1, 1],
+            SubdivisionPattern::Centroid => &[0, 0, 1],
+        }
+    }
+}
+
+/// Splits `triangle` into smaller triangles with the same winding.  The first vertex of each child
+/// is the one at which it connects to the child it is linked beneath.
+fn subdivide(triangle: &TriangleBuf, pattern: SubdivisionPattern) -> Vec<TriangleBuf> {
+    let [a, b, c] = *triangle;
+    match pattern {
+        SubdivisionPattern::Midpoint => {
+            let ab = na::center(&a, &b);
+            let bc = na::center(&b, &c);
+            let ca = na::center(&c, &a);
+            vec![[a, ab, ca], [ab, bc, ca], [ab, b, bc], [bc, c, ca]]
+        }
+        SubdivisionPattern::Centroid => {
+            let center = centroid(triangle);
+            vec![[a, b, center], [b, c, center], [c, a, center]]
+        }
+    }
+}
+
+/// Replaces the triangle at `triangle_ix` with the triangles it subdivides into.  The children
+/// inherit the links of the subdivided triangle so that the chain stays connected: the first child
+/// takes its slot and its parent, and each of its children is re-attached to the child closest to
+/// the vertex it was grown from.
+fn subdivide_triangle(env: &mut Env, triangle_ix: usize, pattern: SubdivisionPattern) {
+    let parent = env.get_triangle(triangle_ix);
+    let (prev_node, next_nodes, depth) = (
+        parent.prev_node,
+        [parent.next_node_1, parent.next_node_2],
+        parent.depth,
+    );
+    let children = subdivide(&parent.geometry, pattern);
+    remove_triangle_geometry(parent);
+
+    let mut child_ixs: Vec<usize> = Vec::with_capacity(children.len());
+    for (i, child) in children.into_iter().enumerate() {
+        let child_ix = if i == 0 {
+            triangle_ix
+        } else {
+            env.get_free_slot_ix()
+        };
+        let size = get_triangle_size(&child);
+        let handle =
+            build_triangle_handle(env, child_ix, get_triangle_bv(&child), child, size, depth);
+        store_triangle_handle(env, child_ix, handle);
+        child_ixs.push(child_ix);
+    }
+
+    env.get_triangle_mut(triangle_ix).prev_node = prev_node;
+    for (i, &link_parent) in pattern.get_link_parents().iter().enumerate().skip(1) {
+        link_child(env, child_ixs[link_parent], child_ixs[i]);
+    }
+    for next_ix in next_nodes.iter().flatten() {
+        let grown_from = env.get_triangle(*next_ix).geometry[0];
+        let closest_child_ix = child_ixs
+            .iter()
+            .copied()
+            .filter(|&child_ix| env.get_triangle(child_ix).has_free_child_slot())
+            .min_by(|&a, &b| {
+                let distance = |child_ix: usize| {
+                    env.get_triangle(child_ix)
+                        .geometry
+                        .iter()
+                        .map(|pt| (pt - grown_from).norm())
+                        .fold(f32::INFINITY, f32::min)
+                };
+                distance(a).total_cmp(&distance(b))
+            })
+            .expect("Subdivided triangle has no children with free slots");
+        link_child(env, closest_child_ix, *next_ix);
+    }
+
+    if env.last_triangle_ix == triangle_ix {
+        env.last_triangle = env.get_triangle(triangle_ix).geometry;
+    }
+}
+
+/// Subdivides a random triangle of the chain, excluding the one that is about to be pruned, whose
+/// children wouldn't be smaller than the minimum size.  Nothing is subdivided while the chain has
+/// `triangle_count` or more triangles so that it stays bounded.
+fn subdivide_random_triangle(env: &mut Env, subdivision: &SubdivisionConf) {
+    if env.live_triangle_count() >= env.conf.triangle_count {
+        return;
+    }
+
+    let candidates: Vec<usize> = env
+        .triangles()
+        .iter()
+        .enumerate()
+        .filter(|(ix, handle)| {
+            *ix != env.oldest_triangle_ix
+                && !handle.removed
+                && subdivide(&handle.geometry, subdivision.pattern)
+                    .iter()
+                    .all(|child| get_triangle_size(child) >= subdivision.min_triangle_size)
+        })
+        .map(|(ix, _)| ix)
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+
+    let triangle_ix = candidates[rng().gen_range(0, candidates.len())];
+    subdivide_triangle(env, triangle_ix, subdivision.pattern);
+}
+
+#[wasm_bindgen]
+pub fn render(chain_ix: usize) {
+    if chunked_world().is_some() {
+        return;
+    }
+    if chain_ix == 0 {
+        reinitialize_global_state();
+    }
+    let env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    if !env.active {
+        return;
+    }
+
+    // place `triangle_count` triangles
+    for _ in 0..env.conf.triangle_count {
+        place_triangle(env, false);
+    }
+}
+
+/// Delete the oldest generated triangle and generate a new triangle.
+#[wasm_bindgen]
+pub fn generate(chain_ix: usize) {
+    if chunked_world().is_some() {
+        return;
+    }
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    if !env.active {
+        return;
+    }
+    if let Some(lifetime) = &mut env.lifetime {
+        if lifetime.remaining == 0 {
+            end_spawned_chain(chain_ix, lifetime.expiry);
+            return;
+        }
+        lifetime.remaining -= 1;
+    }
+    advance_chain(chain_ix);
+}
+
+fn advance_chain(chain_ix: usize) {
+    let env: &mut Env = unsafe { &mut (&mut *ENVS)[chain_ix] };
+    env.generation += 1;
+    // chains that were dormant when they were rendered, or that `render` ran out of room for, fill
+    // their remaining slots before any of their triangles are pruned
+    if env.triangles().len() < env.conf.triangle_count && place_triangle(env, false).is_some() {
+        return;
+    }
+    if env.triangles().is_empty() {
+        return;
+    }
+    // spawned chains grow out from their seed one triangle at a time rather than all being placed
+    // by `render`
+    if env.lifetime.is_some() && env.live_triangle_count() < env.conf.triangle_count {
+        place_triangle(env, false);
+        return;
+    }
+
+    let assert_handle_valid = |handle: &TriangleHandle| {
+        if handle.removed {
+            return;
+        }
         debug_assert!(!(handle.next_node_1 == handle.next_node_2 && handle.next_node_1.is_some()));
         debug_assert!(!(handle.next_node_1 == handle.prev_node && handle.next_node_1.is_some()));
         debug_assert!(!(handle.next_node_2 == handle.prev_node && handle.next_node_2.is_some()));
@@ -596,35 +3956,34 @@ pub fn generate(chain_ix: usize) {
 
     let triangle_valid = if env.oldest_triangle_ix != usize::MAX {
         let oldest_triangle = env.get_triangle(env.oldest_triangle_ix);
-        let triangle_valid = oldest_triangle.degree() == 1
-            && [
-                oldest_triangle.prev_node,
-                oldest_triangle.next_node_1,
-                oldest_triangle.next_node_2,
-            ]
-            .iter()
-            .all(child_degree_is_not_one);
-        if triangle_valid {
-            delete_elem(oldest_triangle.dom_id);
-            world().remove(oldest_triangle.collider_handle);
-            if let Some(prev_ix) = oldest_triangle.prev_node {
-                if env.get_triangle(prev_ix).next_node_1 == Some(env.oldest_triangle_ix) {
-                    env.get_triangle_mut(prev_ix).next_node_1 = None;
-                } else if env.get_triangle(prev_ix).next_node_2 == Some(env.oldest_triangle_ix) {
-                    env.get_triangle_mut(prev_ix).next_node_2 = None;
-                } else {
-                    panic!("Tried to delete triangle but its parent doesn't list it as its child");
-                }
-            }
-            if let Some(child_ix) = oldest_triangle.next_node_1 {
-                debug_assert!(env.get_triangle(child_ix).prev_node == Some(env.oldest_triangle_ix));
-                env.get_triangle_mut(child_ix).prev_node = None;
-            }
-            if let Some(child_ix) = oldest_triangle.next_node_2 {
-                debug_assert!(env.get_triangle(child_ix).prev_node == Some(env.oldest_triangle_ix));
-                env.get_triangle_mut(child_ix).prev_node = None;
+        // triangles that were removed without their slot being refilled can be replaced directly.
+        // Pruning triangles that overlap keep-out rects and other regions can leave triangles with
+        // no links, as can re-seeding a chain that has none left to branch from, and those have no
+        // neighbors to keep attached.  A pair of triangles only linked to each other is kept so
+        // that the chain doesn't die out, unless the chain is over its target size, as it is with
+        // what's left of merged spawned chains.
+        let triangle_valid = oldest_triangle.removed
+            || oldest_triangle.degree() == 0
+            || (oldest_triangle.degree() == 1
+                && (env.live_triangle_count() > env.conf.triangle_count
+                    || [
+                        oldest_triangle.prev_node,
+                        oldest_triangle.next_node_1,
+                        oldest_triangle.next_node_2,
+                    ]
+                    .iter()
+                    .all(child_degree_is_not_one)));
+        if triangle_valid && !oldest_triangle.removed {
+            remove_triangle(env, env.oldest_triangle_ix);
+        }
+        if let Some(subdivision) = env.conf.subdivision.clone() {
+            if triangle_valid && env.generation.is_multiple_of(subdivision.interval) {
+                subdivide_random_triangle(env, &subdivision);
             }
-
+        }
+        // Subdivision and merged spawned chains add triangles to the chain without it growing, so
+        // replacements are only placed while the chain is smaller than its target size
+        if triangle_valid && env.live_triangle_count() < env.conf.triangle_count {
             place_triangle(env, true);
         }
         triangle_valid
@@ -632,14 +3991,17 @@ pub fn generate(chain_ix: usize) {
         false
     };
 
-    if env.oldest_triangle_ix < env.conf.triangle_count - 1 {
+    // The chain usually has `triangle_count` slots, but subdivision and merged spawned chains can
+    // give it more, which need to take their turn at being pruned too, and `render` may have run
+    // out of room before filling them all.
+    if env.oldest_triangle_ix < env.triangles().len() - 1 {
         env.oldest_triangle_ix += 1;
     } else {
         env.oldest_triangle_ix = 0;
     }
 
     if !triangle_valid {
-        generate(chain_ix);
+        advance_chain(chain_ix);
     }
 }
 
@@ -658,3 +4020,704 @@ fn triangle_intersection() {
 
     assert!(check_triangle_collision(&triangle1, &triangle2));
 }
+
+#[test]
+fn touching_triangles_dont_collide() {
+    let parent = [p2(10., 10.), p2(3.875, 20.6), p2(16.125, 20.6)];
+    let child = [parent[1], p2(-6.5, 14.6), p2(-2.25, 26.1)];
+    let mirror = |triangle: &TriangleBuf| triangle.map(|pt| p2(100. - pt.x, pt.y));
+    assert!(!check_triangle_collision(&child, &parent));
+    assert!(!check_triangle_collision(&mirror(&child), &mirror(&parent)));
+
+    // sharing a side, and sharing a vertex while lying inside of the other triangle
+    let neighbor = [parent[2], parent[1], p2(10., 31.2)];
+    assert!(!check_triangle_collision(&neighbor, &parent));
+    let inside = [parent[0], p2(8., 15.), p2(12., 15.)];
+    assert!(check_triangle_collision(&inside, &parent));
+    assert!(check_triangle_collision(&mirror(&parent), &mirror(&inside)));
+}
+
+#[test]
+fn point_triangle_distance() {
+    let triangle = [p2(0., 0.), p2(10., 0.), p2(0., 10.)];
+
+    assert_eq!(distance_to_triangle(p2(2., 2.), &triangle), 0.);
+    assert_eq!(distance_to_triangle(p2(5., -3.), &triangle), 3.);
+    assert_eq!(distance_to_triangle(p2(-4., 13.), &triangle), 5.);
+
+    // segments crossing the triangle without either end inside of it
+    assert_eq!(
+        distance_segment_to_triangle(p2(-5., 2.), p2(15., 2.), &triangle),
+        0.
+    );
+    assert_eq!(
+        distance_segment_to_triangle(p2(-5., -4.), p2(15., -4.), &triangle),
+        4.
+    );
+    assert_eq!(
+        distance_segment_to_triangle(p2(20., 0.), p2(20., 0.), &triangle),
+        10.
+    );
+}
+
+#[test]
+fn subdivided_triangles_cover_parent() {
+    let signed_area = |t: &TriangleBuf| {
+        ((t[1].x - t[0].x) * (t[2].y - t[0].y) - (t[2].x - t[0].x) * (t[1].y - t[0].y)) / 2.
+    };
+    let area = |t: &TriangleBuf| signed_area(t).abs();
+    let triangle = [p2(0., 0.), p2(-6., 10.), p2(6., 10.)];
+
+    for &pattern in &[SubdivisionPattern::Midpoint, SubdivisionPattern::Centroid] {
+        let children = subdivide(&triangle, pattern);
+        assert_eq!(children.len(), pattern.get_link_parents().len());
+        let total_area: f32 = children.iter().map(area).sum();
+        assert!((total_area - area(&triangle)).abs() < 0.001);
+        for (child, &link_parent) in children.iter().zip(pattern.get_link_parents()).skip(1) {
+            assert!(children[link_parent].contains(&child[0]));
+            assert_eq!(signed_area(child).signum(), signed_area(&triangle).signum());
+        }
+    }
+}
+
+#[test]
+fn triangle_rect_collision() {
+    let rect = AABB::new(p2(10., 10.), p2(20., 20.));
+
+    // vertex inside, rect inside triangle, edges crossing with no vertices contained, and disjoint
+    assert!(check_rect_collision(
+        &[p2(15., 15.), p2(30., 30.), p2(30., 15.)],
+        &rect
+    ));
+    assert!(check_rect_collision(
+        &[p2(0., 0.), p2(100., 0.), p2(0., 100.)],
+        &rect
+    ));
+    assert!(check_rect_collision(
+        &[p2(0., 14.), p2(30., 15.), p2(0., 16.)],
+        &rect
+    ));
+    assert!(!check_rect_collision(
+        &[p2(21., 0.), p2(40., 0.), p2(40., 30.)],
+        &rect
+    ));
+}
+
+#[test]
+fn hit_testing_tells_apart_copies_and_reused_slots() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(400, 300);
+    set_horizontal_mirror_symmetry();
+    render(0);
+
+    let (triangle_ix, handle) = triangles(0)
+        .iter()
+        .enumerate()
+        .find(|(_, handle)| !handle.copies.is_empty())
+        .unwrap();
+    let center = centroid(&handle.geometry);
+    let hit = triangle_at(center.x, center.y).unwrap();
+    assert_eq!((hit.chain_ix, hit.triangle_id), (0, triangle_ix));
+    let copy_center = centroid(&handle.copies[0].geometry);
+    assert_eq!(triangle_at(copy_center.x, copy_center.y), Some(hit));
+
+    // removing the triangle leaves nothing to hit, and whatever later fills its slot is stamped
+    // differently
+    let stamp = handle.stamp;
+    add_keep_out_rect(center.x - 0.5, center.y - 0.5, 1., 1.);
+    assert!(triangles(0)[triangle_ix].removed);
+    assert_eq!(triangle_at(center.x, center.y), None);
+    clear_keep_out_rects();
+    for _ in 0..200 {
+        if !triangles(0)[triangle_ix].removed {
+            break;
+        }
+        generate(0);
+    }
+    let replacement = &triangles(0)[triangle_ix];
+    assert!(!replacement.removed);
+    let replacement_center = centroid(&replacement.geometry);
+    let hit = triangle_at(replacement_center.x, replacement_center.y).unwrap();
+    assert_eq!(hit.triangle_id, triangle_ix);
+    assert_ne!(hit.stamp, stamp);
+}
+
+#[test]
+fn spatial_queries() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(400, 300);
+    set_horizontal_mirror_symmetry();
+    for chain_ix in 0..CHAIN_COUNT {
+        render(chain_ix);
+    }
+
+    let mut all: Vec<(usize, usize, usize)> = Vec::new();
+    for chain_ix in 0..CHAIN_COUNT {
+        for (triangle_ix, handle) in triangles(chain_ix).iter().enumerate() {
+            if !handle.removed {
+                all.extend(
+                    (0..=handle.copies.len()).map(|copy_ix| (chain_ix, triangle_ix, copy_ix)),
+                );
+            }
+        }
+    }
+    let geometry = |&(chain_ix, triangle_ix, copy_ix): &(usize, usize, usize)| {
+        *get_triangle(chain_ix, triangle_ix).get_geometry(copy_ix)
+    };
+    let ids = |results: &[QueriedTriangle]| -> Vec<(usize, usize, usize)> {
+        for result in results {
+            let leaf = (result.chain_ix, result.triangle_id, result.copy_ix);
+            assert_eq!(result.geometry, geometry(&leaf));
+            assert_eq!(result.stamp, get_triangle(leaf.0, leaf.1).stamp);
+        }
+        results
+            .iter()
+            .map(|result| (result.chain_ix, result.triangle_id, result.copy_ix))
+            .collect()
+    };
+    let matching = |is_match: &dyn Fn(&TriangleBuf) -> bool| -> Vec<(usize, usize, usize)> {
+        all.iter()
+            .copied()
+            .filter(|leaf| is_match(&geometry(leaf)))
+            .collect()
+    };
+    assert!(all.iter().any(|&(_, _, copy_ix)| copy_ix != 0));
+
+    let rect = AABB::new(p2(100., 50.), p2(300., 150.));
+    let in_rect = ids(&triangles_in_rect(100., 50., 200., 100.));
+    assert!(!in_rect.is_empty());
+    assert_eq!(
+        in_rect,
+        matching(&|triangle| check_rect_collision(triangle, &rect))
+    );
+
+    let center = p2(200., 150.);
+    let in_radius = ids(&triangles_in_radius(center.x, center.y, 60.));
+    assert_eq!(
+        in_radius,
+        matching(&|triangle| distance_to_triangle(center, triangle) <= 60.)
+    );
+
+    let polygon = [50., 50., 350., 100., 50., 250.];
+    let in_polygon = ids(&triangles_in_polygon(&polygon).unwrap());
+    let mask = build_polygon_mask(&polygon).unwrap();
+    assert_eq!(
+        in_polygon,
+        matching(&|triangle| mask.intersects_triangle(triangle))
+    );
+    assert!(triangles_in_polygon(&[0., 0., 1., 1.]).is_err());
+
+    // nearest neighbors come closest first, and asking for more than there are returns them all
+    assert!(nearest_triangles(center.x, center.y, 0).is_empty());
+    let nearest = ids(&nearest_triangles(center.x, center.y, 10));
+    assert_eq!(nearest.len(), 10);
+    let distances: Vec<f32> = nearest
+        .iter()
+        .map(|leaf| distance_to_triangle(center, &geometry(leaf)))
+        .collect();
+    assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+    let mut sorted_distances: Vec<f32> = all
+        .iter()
+        .map(|leaf| distance_to_triangle(center, &geometry(leaf)))
+        .collect();
+    sorted_distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(distances, sorted_distances[..10]);
+    let mut everything = ids(&nearest_triangles(center.x, center.y, all.len() + 5));
+    everything.sort_unstable();
+    assert_eq!(everything, all);
+}
+
+#[test]
+fn spawned_chains_respect_limits_reuse_slots_and_merge() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(1000, 800);
+    for chain_ix in 0..CHAIN_COUNT {
+        render(chain_ix);
+    }
+    let free_spots: Vec<Point2<f32>> = (1..10)
+        .flat_map(|x| (1..8).map(move |y| p2(x as f32 * 100., y as f32 * 100.)))
+        .filter(|pt| triangles_in_radius(pt.x, pt.y, 40.).is_empty())
+        .collect();
+    assert!(free_spots.len() >= 3);
+    let spawn = |pt: Point2<f32>, lifetime: usize, expiry: ChainExpiry| {
+        spawn_chain(pt.x, pt.y, "red", "blue", lifetime, expiry)
+    };
+    let envs = || unsafe { &*ENVS };
+
+    // the second chain only gets the triangles left under the limit, and there is no room for a
+    // third
+    set_spawned_chain_limits(2, 60);
+    assert_eq!(
+        spawn(free_spots[0], 3, ChainExpiry::Remove),
+        Some(CHAIN_COUNT)
+    );
+    assert_eq!(
+        spawn(free_spots[1], 30, ChainExpiry::Merge),
+        Some(CHAIN_COUNT + 1)
+    );
+    assert_eq!(spawn(free_spots[2], 30, ChainExpiry::Remove), None);
+    assert_eq!(envs()[CHAIN_COUNT + 1].conf.triangle_count, 10);
+
+    // the first chain is removed after being generated `lifetime` times and its index reused
+    for _ in 0..4 {
+        generate_spawned_chains();
+    }
+    assert!(!envs()[CHAIN_COUNT].active);
+    assert!(triangles(CHAIN_COUNT).is_empty());
+    assert_eq!(
+        spawn(free_spots[2], 30, ChainExpiry::Remove),
+        Some(CHAIN_COUNT)
+    );
+
+    // the merged chain's triangles keep their links and can be found at their new slots, which were
+    // either removed or past the end of the target chain
+    let merged_ix = CHAIN_COUNT + 1;
+    while envs()[merged_ix].lifetime.unwrap().remaining != 0 {
+        generate(merged_ix);
+    }
+    let target_ix = find_closest_permanent_chain(envs()[merged_ix].seed_point).unwrap();
+    let merged: Vec<(TriangleBuf, Option<TriangleBuf>)> = triangles(merged_ix)
+        .iter()
+        .filter(|handle| !handle.removed)
+        .map(|handle| {
+            let parent = handle.prev_node.map(|ix| triangles(merged_ix)[ix].geometry);
+            (handle.geometry, parent)
+        })
+        .collect();
+    assert!(merged.len() > 1);
+    let was_removed: Vec<bool> = triangles(target_ix)
+        .iter()
+        .map(|handle| handle.removed)
+        .collect();
+    generate(merged_ix);
+    assert!(!envs()[merged_ix].active);
+    assert!(triangles(merged_ix).is_empty());
+    for (geometry, parent) in merged {
+        let triangle_ix = triangles(target_ix)
+            .iter()
+            .position(|handle| !handle.removed && handle.geometry == geometry)
+            .unwrap();
+        assert!(was_removed.get(triangle_ix).copied().unwrap_or(true));
+        let handle = &triangles(target_ix)[triangle_ix];
+        assert_eq!(
+            handle.prev_node.map(|ix| triangles(target_ix)[ix].geometry),
+            parent
+        );
+        let center = centroid(&geometry);
+        let hit = triangle_at(center.x, center.y).unwrap();
+        assert_eq!((hit.chain_ix, hit.triangle_id), (target_ix, triangle_ix));
+    }
+
+    // re-rendering the field ends every spawned chain
+    reinitialize_global_state();
+    assert_eq!(envs().len(), CHAIN_COUNT);
+}
+
+#[test]
+fn repeated_merges_keep_the_target_chain_bounded() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(1000, 800);
+    render(0);
+    let envs = || unsafe { &*ENVS };
+    let triangle_count = envs()[0].conf.triangle_count;
+    set_spawned_chain_limits(1, 30);
+
+    let mut merge_count = 0;
+    for cycle in 0..40 {
+        let spot = (1..10)
+            .flat_map(|x| (1..8).map(move |y| p2(x as f32 * 100., y as f32 * 100.)))
+            .cycle()
+            .skip(cycle * 7)
+            .take(63)
+            .find(|pt| triangles_in_radius(pt.x, pt.y, 40.).is_empty());
+        let spot = match spot {
+            Some(spot) => spot,
+            None => continue,
+        };
+        let chain_ix = spawn_chain(spot.x, spot.y, "red", "blue", 40, ChainExpiry::Merge).unwrap();
+        let target_ix = find_closest_permanent_chain(spot);
+        while envs()[chain_ix].active {
+            generate(chain_ix);
+        }
+        if target_ix == Some(0) {
+            merge_count += 1;
+        }
+        for _ in 0..200 {
+            generate(0);
+        }
+
+        // the target can be over its size by the merged triangles, with as many removed slots left
+        // behind by pruning
+        assert!(triangles(0).len() <= triangle_count + 2 * 30);
+        assert_world_consistent();
+    }
+    assert!(merge_count >= 20);
+}
+
+#[test]
+fn dla_triangles_stick_to_their_parents() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(300, 200);
+    set_diffusion_limited_aggregation(0, 4., 3., 2000);
+    render(0);
+
+    let chain = triangles(0);
+    assert!(chain.iter().filter(|handle| !handle.removed).count() > 10);
+    for (triangle_ix, handle) in chain.iter().enumerate() {
+        let parent_ix = match handle.prev_node {
+            Some(parent_ix) if !handle.removed => parent_ix,
+            _ => continue,
+        };
+        let parent = &chain[parent_ix];
+        assert!([parent.next_node_1, parent.next_node_2].contains(&Some(triangle_ix)));
+        let distance = (0..3)
+            .map(|i| {
+                let (a, b) = (handle.geometry[i], handle.geometry[(i + 1) % 3]);
+                distance_segment_to_triangle(a, b, &parent.geometry)
+            })
+            .fold(f32::INFINITY, f32::min);
+        assert!(distance <= 3. + 0.001);
+        for (other_ix, other) in chain.iter().enumerate() {
+            if other_ix != triangle_ix && !other.removed {
+                assert!(!check_triangle_collision(&handle.geometry, &other.geometry));
+            }
+        }
+    }
+}
+
+#[test]
+fn gap_fill_packs_smaller_triangles_against_wrapped_copies() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(100, 80);
+    set_wrap(true);
+    set_gap_fill(0, 0.7, 3.);
+    let conf = unsafe { &mut (&mut *ENVS)[0].conf };
+    conf.triangle_count = 70;
+    let full_size = conf.triangle_size;
+    render(0);
+
+    let chain = triangles(0);
+    let live = || {
+        chain
+            .iter()
+            .enumerate()
+            .filter(|(_, handle)| !handle.removed)
+    };
+    let mut filled = 0;
+    for (_, handle) in live().filter(|(_, handle)| handle.size < full_size) {
+        filled += 1;
+        let parent = &chain[handle.prev_node.unwrap()];
+        let shares_vertex = (0..=parent.copies.len()).any(|copy_ix| {
+            let geometry = parent.get_geometry(copy_ix);
+            handle.geometry.iter().any(|pt| geometry.contains(pt))
+        });
+        assert!(shares_vertex);
+    }
+    assert!(filled > 0);
+
+    // no triangle overlaps any other one, or any wrapped copy of any triangle
+    let placed: Vec<(usize, TriangleBuf)> = live()
+        .flat_map(|(triangle_ix, handle)| {
+            (0..=handle.copies.len())
+                .map(move |copy_ix| (triangle_ix, *handle.get_geometry(copy_ix)))
+        })
+        .collect();
+    assert!(placed.len() > live().count());
+    for (i, (_, triangle)) in placed.iter().enumerate() {
+        for (_, other) in &placed[i + 1..] {
+            assert!(!check_triangle_collision(triangle, other));
+        }
+    }
+}
+
+#[test]
+fn path_masks_must_enclose_an_area() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(400, 300);
+    render(0);
+    let live_count = || triangles(0).iter().filter(|handle| !handle.removed).count();
+    let before = live_count();
+
+    for d in &["", "M 10 10 L 200 200"] {
+        assert!(set_allowed_path(d, FillRule::NonZero, 0., 0., 1.).is_err());
+        assert!(add_forbidden_path(d, FillRule::NonZero, 0., 0., 1.).is_err());
+    }
+    assert!(field_conf().allowed_region.is_none());
+    assert!(field_conf().forbidden_regions.is_empty());
+    assert_eq!(live_count(), before);
+
+    assert_eq!(
+        add_forbidden_path("M 0 0 H 400 V 300 H 0 Z", FillRule::NonZero, 0., 0., 1.),
+        Ok(0)
+    );
+    assert_eq!(live_count(), 0);
+}
+
+/// Checks that every live triangle and copy of the field, and nothing else, is in the collision
+/// world where it currently lies
+#[cfg(test)]
+fn assert_world_consistent() {
+    let mut expected: Vec<(usize, usize, usize)> = Vec::new();
+    for env in unsafe { &*ENVS }.iter().filter(|env| env.active) {
+        for (triangle_ix, handle) in env.triangles().iter().enumerate() {
+            if handle.removed {
+                continue;
+            }
+            expected.extend(
+                (0..=handle.copies.len()).map(|copy_ix| (env.chain_ix, triangle_ix, copy_ix)),
+            );
+            let center = centroid(&handle.geometry);
+            let hit = triangle_at(center.x, center.y).unwrap();
+            assert_eq!(
+                (hit.chain_ix, hit.triangle_id, hit.stamp),
+                (env.chain_ix, triangle_ix, handle.stamp)
+            );
+        }
+    }
+    expected.sort_unstable();
+    let everywhere = AABB::new(p2(-1.0e6, -1.0e6), p2(1.0e6, 1.0e6));
+    assert_eq!(query_triangles(&everywhere, |_| true), expected);
+}
+
+#[test]
+fn resizing_clamps_tiny_canvases_and_grows_into_new_space() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(200, 150);
+    render(0);
+    let conf = || unsafe { &(&*ENVS)[0].conf };
+    let live = || triangles(0).iter().filter(|handle| !handle.removed);
+
+    // 2 triangles wide with a unit of margin
+    resize(1, 0, ResizePolicy::Prune);
+    assert_eq!((conf().canvas_width, conf().canvas_height), (26, 26));
+    let canvas = AABB::new(p2(0., 0.), p2(26., 26.));
+    assert!(live().all(|handle| canvas.contains(&get_triangle_bv(&handle.geometry))));
+    assert_world_consistent();
+
+    resize(400, 300, ResizePolicy::Prune);
+    for _ in 0..300 {
+        generate(0);
+    }
+    assert_eq!(live().count(), conf().triangle_count);
+    assert!(live().any(|handle| !canvas.contains(&get_triangle_bv(&handle.geometry))));
+    assert_world_consistent();
+}
+
+#[test]
+fn exports_follow_the_display_scale_and_device_pixel_ratio() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(200, 100);
+    render(0);
+
+    // the canvas keeps covering 200 by 100 CSS pixels, which are now 100 by 50 world units
+    set_display_scale(2.);
+    let conf = unsafe { &(&*ENVS)[0].conf };
+    assert_eq!((conf.canvas_width, conf.canvas_height), (100, 50));
+    let handle = triangles(0).iter().find(|handle| !handle.removed).unwrap();
+
+    let svg = export_svg_tile();
+    assert!(svg.contains("width=\"200\" height=\"100\""));
+    let [pt, ..] = handle.geometry.map(|pt| pt * 2.);
+    assert!(svg.contains(&format!("points=\"{},{} ", pt.x, pt.y)));
+
+    // 1.5 device pixels per CSS pixel make 3 per world unit
+    let image = Image::decode_png(&export_png_tile(1.5).unwrap()).unwrap();
+    assert_eq!((image.width, image.height), (300, 150));
+    let center = centroid(&handle.geometry) * 3.;
+    let [r, g, b, a] = image.get_pixel(center.x as usize, center.y as usize);
+    assert_eq!(Some([r, g, b]), export::parse_color(&handle.color));
+    assert_eq!(a, 255);
+}
+
+#[test]
+fn resize_policies_move_or_prune_triangles() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let policies = [
+        ResizePolicy::Prune,
+        ResizePolicy::Scale,
+        ResizePolicy::AnchorTopRight,
+        ResizePolicy::AnchorBottomLeft,
+        ResizePolicy::AnchorBottomRight,
+        ResizePolicy::AnchorCenter,
+    ];
+    let (old_size, new_size) = (Vector2::new(300., 200.), Vector2::new(240., 260.));
+    for &policy in &policies {
+        init_triangles(300, 200);
+        set_chain_seed(0, 150., 100., 0.);
+        for chain_ix in 0..CHAIN_COUNT {
+            render(chain_ix);
+        }
+        // `(chain_ix, triangle_ix, stamp, size, geometry)` of every triangle
+        let before: Vec<(usize, usize, usize, f32, TriangleBuf)> = (0..CHAIN_COUNT)
+            .flat_map(|chain_ix| {
+                triangles(chain_ix)
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, handle)| !handle.removed)
+                    .map(move |(triangle_ix, handle)| {
+                        let (stamp, size) = (handle.stamp, handle.size);
+                        (chain_ix, triangle_ix, stamp, size, handle.geometry)
+                    })
+            })
+            .collect();
+        let seed_point = unsafe { (&*ENVS)[0].seed_point };
+
+        resize(240, 260, policy);
+        let transform = |pt: Point2<f32>| policy.transform_point(pt, old_size, new_size);
+        assert_eq!(unsafe { (&*ENVS)[0].seed_point }, transform(seed_point));
+        let on_canvas =
+            |pt: &Point2<f32>| pt.x > 0. && pt.x < new_size.x && pt.y > 0. && pt.y < new_size.y;
+        let size_scale = (new_size.x * new_size.y / (old_size.x * old_size.y)).sqrt();
+        let (mut kept, mut pruned) = (0, 0);
+        for (chain_ix, triangle_ix, stamp, size, geometry) in before {
+            let moved = geometry.map(transform);
+            let handle = &triangles(chain_ix)[triangle_ix];
+            if handle.removed {
+                // stretched triangles can also be pruned for colliding with their neighbors
+                assert!(policy == ResizePolicy::Scale || !moved.iter().all(on_canvas));
+                pruned += 1;
+                continue;
+            }
+            kept += 1;
+            assert!(moved.iter().all(on_canvas));
+            assert_eq!((handle.stamp, handle.geometry), (stamp, moved));
+            if policy == ResizePolicy::Scale {
+                assert!((handle.size - size * size_scale).abs() < 0.001);
+            } else {
+                assert_eq!(handle.size, size);
+            }
+        }
+        assert!(kept > 0);
+        assert!(pruned > 0 || policy == ResizePolicy::Scale);
+        assert_world_consistent();
+    }
+}
+
+#[test]
+fn territories_and_leashes_confine_their_chains() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(400, 300);
+    let conf = |chain_ix: usize| unsafe { &(&*ENVS)[chain_ix].conf };
+
+    // bounds need to be more than two triangle sizes across
+    assert!(set_chain_territory_rect(0, 10., 10., 20., 100.).is_err());
+    assert!(set_chain_territory_polygon(0, &[10., 10., 100., 10., 10., 30.]).is_err());
+    assert!(set_chain_leash(1, 12.).is_err());
+    assert!(conf(0).territory.is_none() && conf(1).leash.is_none());
+
+    let territory = AABB::new(p2(50., 50.), p2(200., 150.));
+    set_chain_territory_rect(0, 50., 50., 150., 100.).unwrap();
+    set_chain_seed(1, 300., 150., 0.);
+    set_chain_leash(1, 60.).unwrap();
+    let leash = conf(1).leash.unwrap();
+    assert_eq!(leash.center, unsafe { (&*ENVS)[1].seed_point });
+    for chain_ix in 0..CHAIN_COUNT {
+        render(chain_ix);
+    }
+    for _ in 0..200 {
+        (0..CHAIN_COUNT).for_each(generate);
+    }
+
+    let live = |chain_ix: usize| triangles(chain_ix).iter().filter(|handle| !handle.removed);
+    assert!(live(0).count() > 10 && live(1).count() > 10);
+    assert!(live(0).all(|handle| territory.contains(&get_triangle_bv(&handle.geometry))));
+    assert!(live(1).all(|handle| {
+        handle
+            .geometry
+            .iter()
+            .all(|pt| (pt - leash.center).norm() <= leash.radius)
+    }));
+}
+
+#[test]
+fn pointer_repulsion_retracts_tips_before_cutting_branches() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(400, 300);
+    for radius in [0., -5., f32::NAN, f32::INFINITY] {
+        assert!(set_pointer_repulsion(radius, 0.).is_err());
+    }
+    assert!(field_conf().pointer_repulsion.is_none());
+
+    // a single branch of small triangles in a row, each linked to the one to its left
+    let env = unsafe { &mut (&mut *ENVS)[0] };
+    for i in 0..10 {
+        let x = 20. + 20. * i as f32;
+        let triangle = [p2(x, 100.), p2(x + 10., 100.), p2(x + 5., 91.)];
+        insert_triangle(env, false, get_triangle_bv(&triangle), triangle, 10.);
+    }
+    let dom_ids: Vec<usize> = triangles(0).iter().map(|handle| handle.dom_id).collect();
+    let take_deleted =
+        || mem::take(&mut *DELETED_ELEMS.lock().unwrap_or_else(|err| err.into_inner()));
+    take_deleted();
+
+    // sweeping over the last 4 triangles retracts the branch from its tip inward
+    set_pointer_repulsion(1., 1.).unwrap();
+    update_pointer(138., 95., 100., 0.);
+    assert_eq!(take_deleted(), [9, 8, 7, 6].map(|ix| dom_ids[ix]));
+    assert_eq!(triangles(0)[5].degree(), 1);
+
+    // with no tips in the repelled area, the branch is cut and the part beyond it is left in place
+    update_pointer(58., 95., 40., 0.);
+    let mut deleted = take_deleted();
+    deleted.sort_unstable();
+    assert_eq!(deleted, [dom_ids[2], dom_ids[3]]);
+    assert_eq!(triangles(0)[1].next_node_1, None);
+    assert_eq!(triangles(0)[4].prev_node, None);
+    assert!(!triangles(0)[4].removed && !triangles(0)[5].removed);
+
+    // the pointer moves along with the field when it is resized
+    resize(800, 600, ResizePolicy::Scale);
+    let pointer = field_conf().pointer_repulsion.unwrap().pointer;
+    assert_eq!(pointer, Some((p2(116., 190.), Vector2::new(80., 0.))));
+}
+
+#[test]
+fn target_seeking_chains_branch_and_steer_toward_their_target() {
+    let _lock = FIELD_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    init_triangles(400, 300);
+    set_target_seeking(0, 100, 1.);
+
+    // a single branch of small triangles in a row, each linked to the one to its left
+    let env = unsafe { &mut (&mut *ENVS)[0] };
+    for i in 0..10 {
+        let x = 20. + 20. * i as f32;
+        let triangle = [p2(x, 100.), p2(x + 10., 100.), p2(x + 5., 91.)];
+        insert_triangle(env, false, get_triangle_bv(&triangle), triangle, 10.);
+    }
+
+    // with no target the chain falls back to a random branch point
+    assert!(!env.select_seeking_branch(100, 1.));
+
+    // the triangle closest to the target is branched from, and new triangles are steered toward it
+    for (target, closest_ix) in [(p2(350., 200.), 9), (p2(0., 0.), 0), (p2(105., 50.), 4)] {
+        set_chain_target(0, target.x, target.y);
+        env.set_new_last_triangle();
+        assert_eq!(env.last_triangle_ix, closest_ix);
+        let offset = target - centroid(&triangles(0)[closest_ix].geometry);
+        let (angle, strength) = env.steering.unwrap();
+        assert!((angle - offset.y.atan2(offset.x)).abs() < 1e-4);
+        assert_eq!(strength, 1.);
+    }
+    clear_chain_target(0);
+    assert!(env.steering.is_none());
+
+    // a rendered chain grows closer to its target than a random one does
+    let closest_distance = |seeking: bool| {
+        init_triangles(600, 400);
+        if seeking {
+            set_target_seeking(0, 8, 1.);
+            set_chain_target(0, 560., 360.);
+        }
+        render(0);
+        for _ in 0..300 {
+            generate(0);
+        }
+        triangles(0)
+            .iter()
+            .filter(|handle| !handle.removed)
+            .map(|handle| distance_to_triangle(p2(560., 360.), &handle.geometry))
+            .fold(f32::INFINITY, f32::min)
+    };
+    let (random, seeking) = (closest_distance(false), closest_distance(true));
+    assert!(seeking < random / 2.);
+}
@@ -0,0 +1,644 @@
+//! Regions of the canvas defined by SVG paths, flattened into polygons so that triangles can be
+//! tested against them.
+
+use std::f32;
+
+use ncollide2d::bounding_volume::{aabb::AABB, BoundingVolume};
+use ncollide2d::na::{self, Point2, Vector2};
+use wasm_bindgen::prelude::*;
+
+use super::{check_line_seg_intersection, p2, point_in_triangle, TriangleBuf};
+
+/// Curves and arcs are flattened into line segments no longer than this
+const MAX_SEGMENT_LENGTH: f32 = 3.;
+/// Maximum number of line segments that a single curve or arc is flattened into
+const MAX_CURVE_SEGMENTS: usize = 128;
+
+/// Determines which points are inside of a path whose subpaths overlap or nest, matching the SVG
+/// `fill-rule` property
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+/// A filled region made up of one or more closed polygons
+#[derive(Clone, Debug)]
+pub struct Mask {
+    pub polygons: Vec<Vec<Point2<f32>>>,
+    /// Bounds of each polygon, used to skip polygons that are far away from the shapes being tested
+    pub polygon_bounds: Vec<AABB<f32>>,
+    pub fill_rule: FillRule,
+    pub bounds: AABB<f32>,
+}
+
+fn get_points_bv(points: &[Point2<f32>]) -> AABB<f32> {
+    let mut bv = AABB::new(points[0], points[0]);
+    for pt in &points[1..] {
+        bv.take_point(*pt);
+    }
+    bv
+}
+
+impl Mask {
+    pub fn new(polygons: Vec<Vec<Point2<f32>>>, fill_rule: FillRule) -> Self {
+        let polygons: Vec<Vec<Point2<f32>>> = polygons
+            .into_iter()
+            .filter(|polygon| polygon.len() >= 3)
+            .collect();
+        let polygon_bounds: Vec<AABB<f32>> = polygons
+            .iter()
+            .map(|polygon| get_points_bv(polygon))
+            .collect();
+        let bounds = polygon_bounds
+            .iter()
+            .copied()
+            .reduce(|a, b| a.merged(&b))
+            .unwrap_or_else(|| AABB::new(Point2::origin(), Point2::origin()));
+
+        Mask {
+            polygons,
+            polygon_bounds,
+            fill_rule,
+            bounds,
+        }
+    }
+
+    /// Parses the SVG path data string `d` into a mask, scaling its coordinates by `scale` and then
+    /// offsetting them by `offset`.  All subpaths are treated as closed.
+    pub fn from_svg_path(
+        d: &str,
+        fill_rule: FillRule,
+        offset: Vector2<f32>,
+        scale: f32,
+    ) -> Result<Self, String> {
+        let polygons = parse_svg_path(d)?
+            .into_iter()
+            .map(|polygon| polygon.into_iter().map(|pt| pt * scale + offset).collect())
+            .collect();
+        Ok(Mask::new(polygons, fill_rule))
+    }
+
+    /// Builds a mask covering the ellipse centered at `center` with radii `radii`
+    pub fn ellipse(center: Point2<f32>, radii: Vector2<f32>) -> Self {
+        let mut polygon = Vec::new();
+        push_axis_aligned_arc(&mut polygon, center, radii, 0., f32::consts::TAU);
+        polygon.pop();
+        Mask::new(vec![polygon], FillRule::NonZero)
+    }
+
+    /// Builds a mask covering `rect` with its corners rounded off by circular arcs of `radius`,
+    /// which is limited to half of the rectangle's shorter side
+    pub fn rounded_rect(rect: &AABB<f32>, radius: f32) -> Self {
+        let (mins, maxs) = (rect.mins, rect.maxs);
+        let radius = radius
+            .min((maxs.x - mins.x) / 2.)
+            .min((maxs.y - mins.y) / 2.)
+            .max(0.);
+        if radius == 0. {
+            return Mask::polygon(vec![mins, p2(maxs.x, mins.y), maxs, p2(mins.x, maxs.y)]);
+        }
+
+        let radii = Vector2::new(radius, radius);
+        let corners = [
+            (
+                p2(maxs.x - radius, mins.y + radius),
+                -f32::consts::FRAC_PI_2,
+            ),
+            (p2(maxs.x - radius, maxs.y - radius), 0.),
+            (p2(mins.x + radius, maxs.y - radius), f32::consts::FRAC_PI_2),
+            (p2(mins.x + radius, mins.y + radius), f32::consts::PI),
+        ];
+        let mut polygon = Vec::new();
+        for &(center, start_angle) in &corners {
+            push_axis_aligned_arc(
+                &mut polygon,
+                center,
+                radii,
+                start_angle,
+                f32::consts::FRAC_PI_2,
+            );
+        }
+        Mask::new(vec![polygon], FillRule::NonZero)
+    }
+
+    /// Builds a mask covering the polygon with vertices `points`, which may be given in either
+    /// winding order
+    pub fn polygon(points: Vec<Point2<f32>>) -> Self {
+        Mask::new(vec![points], FillRule::NonZero)
+    }
+
+    /// Returns the polygons whose bounds satisfy `filter`
+    fn polygons_where<'a>(
+        &'a self,
+        filter: impl Fn(&AABB<f32>) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a Vec<Point2<f32>>> + 'a {
+        self.polygons
+            .iter()
+            .zip(&self.polygon_bounds)
+            .filter(move |(_, bounds)| filter(bounds))
+            .map(|(polygon, _)| polygon)
+    }
+
+    /// Iterates over the edges of `polygon`, including the closing edge
+    fn edges(polygon: &[Point2<f32>]) -> impl Iterator<Item = (Point2<f32>, Point2<f32>)> + '_ {
+        polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .map(|(a, b)| (*a, *b))
+    }
+
+    pub fn contains_point(&self, pt: Point2<f32>) -> bool {
+        if pt.x < self.bounds.mins.x
+            || pt.x > self.bounds.maxs.x
+            || pt.y < self.bounds.mins.y
+            || pt.y > self.bounds.maxs.y
+        {
+            return false;
+        }
+
+        // only polygons that a ray cast from the point in the +x direction could cross affect the
+        // winding number
+        let mut winding_number = 0i32;
+        let crossable = |bounds: &AABB<f32>| {
+            pt.x <= bounds.maxs.x && pt.y >= bounds.mins.y && pt.y <= bounds.maxs.y
+        };
+        for polygon in self.polygons_where(crossable) {
+            for (a, b) in Mask::edges(polygon) {
+                let side = (b.x - a.x) * (pt.y - a.y) - (pt.x - a.x) * (b.y - a.y);
+                if a.y <= pt.y && b.y > pt.y && side > 0. {
+                    winding_number += 1;
+                } else if a.y > pt.y && b.y <= pt.y && side < 0. {
+                    winding_number -= 1;
+                }
+            }
+        }
+
+        match self.fill_rule {
+            FillRule::NonZero => winding_number != 0,
+            FillRule::EvenOdd => winding_number % 2 != 0,
+        }
+    }
+
+    fn check_boundary_intersection(&self, triangle: &TriangleBuf, triangle_bv: &AABB<f32>) -> bool {
+        let triangle_edges = [
+            (triangle[0], triangle[1]),
+            (triangle[1], triangle[2]),
+            (triangle[2], triangle[0]),
+        ];
+        self.polygons_where(|bounds| bounds.intersects(triangle_bv))
+            .flat_map(|polygon| Mask::edges(polygon))
+            .any(|(a, b)| {
+                triangle_edges
+                    .iter()
+                    .any(|&(t1, t2)| check_line_seg_intersection(a, b, t1, t2))
+            })
+    }
+
+    /// Returns `true` if `triangle` lies entirely inside of the mask
+    pub fn contains_triangle(&self, triangle: &TriangleBuf) -> bool {
+        triangle.iter().all(|pt| self.contains_point(*pt))
+            && !self.check_boundary_intersection(triangle, &get_points_bv(triangle))
+    }
+
+    /// Returns `true` if any part of `triangle` lies inside of the mask
+    pub fn intersects_triangle(&self, triangle: &TriangleBuf) -> bool {
+        let triangle_bv = get_points_bv(triangle);
+        if !self.bounds.intersects(&triangle_bv) {
+            return false;
+        }
+
+        triangle.iter().any(|pt| self.contains_point(*pt))
+            || self
+                .polygons_where(|bounds| bounds.intersects(&triangle_bv))
+                .flatten()
+                .any(|pt| point_in_triangle(*pt, triangle))
+            || self.check_boundary_intersection(triangle, &triangle_bv)
+    }
+}
+
+/// Reads numbers and flags out of SVG path data, which may be separated by whitespace, commas, or
+/// nothing at all when unambiguous (`M1-2.5.5` is `M 1 -2.5 0.5`).
+struct PathTokenizer<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PathTokenizer<'a> {
+    fn skip_separators(&mut self) {
+        while self.pos < self.data.len()
+            && (self.data[self.pos].is_ascii_whitespace() || self.data[self.pos] == b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// Returns the next command letter without consuming it, if the next token is one
+    fn peek_command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        self.data
+            .get(self.pos)
+            .copied()
+            .filter(|c| c.is_ascii_alphabetic() && *c != b'e' && *c != b'E')
+    }
+
+    fn is_done(&mut self) -> bool {
+        self.skip_separators();
+        self.pos >= self.data.len()
+    }
+
+    fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        matches!(self.data.get(self.pos), Some(c) if c.is_ascii_digit() || b"+-.".contains(c))
+    }
+
+    fn number(&mut self) -> Result<f32, String> {
+        self.skip_separators();
+        let start = self.pos;
+        let data = self.data;
+        let mut pos = self.pos;
+        if pos < data.len() && (data[pos] == b'+' || data[pos] == b'-') {
+            pos += 1;
+        }
+        let mut seen_dot = false;
+        while pos < data.len() && (data[pos].is_ascii_digit() || (data[pos] == b'.' && !seen_dot)) {
+            seen_dot |= data[pos] == b'.';
+            pos += 1;
+        }
+        if pos < data.len() && (data[pos] == b'e' || data[pos] == b'E') {
+            let mut exponent_end = pos + 1;
+            if exponent_end < data.len()
+                && (data[exponent_end] == b'+' || data[exponent_end] == b'-')
+            {
+                exponent_end += 1;
+            }
+            if exponent_end < data.len() && data[exponent_end].is_ascii_digit() {
+                pos = exponent_end;
+                while pos < data.len() && data[pos].is_ascii_digit() {
+                    pos += 1;
+                }
+            }
+        }
+
+        let token = std::str::from_utf8(&data[start..pos]).unwrap();
+        let val = token
+            .parse::<f32>()
+            .map_err(|_| format!("Expected a number at position {} of path data", start))?;
+        self.pos = pos;
+        Ok(val)
+    }
+
+    fn point(&mut self) -> Result<Vector2<f32>, String> {
+        Ok(Vector2::new(self.number()?, self.number()?))
+    }
+
+    /// Arc flags are single `0` or `1` characters that don't need to be separated from what follows
+    fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+        let flag = match self.data.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => {
+                return Err(format!(
+                    "Expected an arc flag at position {} of path data",
+                    self.pos
+                ))
+            }
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+}
+
+fn get_segment_count(length: f32) -> usize {
+    ((length / MAX_SEGMENT_LENGTH).ceil() as usize).clamp(1, MAX_CURVE_SEGMENTS)
+}
+
+pub fn flatten_cubic(
+    polygon: &mut Vec<Point2<f32>>,
+    p0: Point2<f32>,
+    p1: Point2<f32>,
+    p2: Point2<f32>,
+    p3: Point2<f32>,
+) {
+    let segments = get_segment_count((p1 - p0).norm() + (p2 - p1).norm() + (p3 - p2).norm());
+    for i in 1..=segments {
+        let t = i as f32 / segments as f32;
+        let mt = 1. - t;
+        polygon.push(Point2::from(
+            p0.coords * (mt * mt * mt)
+                + p1.coords * (3. * mt * mt * t)
+                + p2.coords * (3. * mt * t * t)
+                + p3.coords * (t * t * t),
+        ));
+    }
+}
+
+pub fn flatten_quadratic(
+    polygon: &mut Vec<Point2<f32>>,
+    p0: Point2<f32>,
+    p1: Point2<f32>,
+    p2: Point2<f32>,
+) {
+    let segments = get_segment_count((p1 - p0).norm() + (p2 - p1).norm());
+    for i in 1..=segments {
+        let t = i as f32 / segments as f32;
+        let mt = 1. - t;
+        polygon.push(Point2::from(
+            p0.coords * (mt * mt) + p1.coords * (2. * mt * t) + p2.coords * (t * t),
+        ));
+    }
+}
+
+/// Flattens the arc of the axis-aligned ellipse centered at `center` with radii `radii` from
+/// `start_angle` through `sweep_angle` radians, including both of its endpoints
+fn push_axis_aligned_arc(
+    polygon: &mut Vec<Point2<f32>>,
+    center: Point2<f32>,
+    radii: Vector2<f32>,
+    start_angle: f32,
+    sweep_angle: f32,
+) {
+    let segments = get_segment_count(sweep_angle.abs() * radii.x.max(radii.y));
+    for i in 0..=segments {
+        let theta = start_angle + sweep_angle * i as f32 / segments as f32;
+        polygon.push(center + Vector2::new(radii.x * theta.cos(), radii.y * theta.sin()));
+    }
+}
+
+/// Flattens an elliptical arc given in SVG's endpoint parameterization, following the conversion to
+/// center parameterization from the SVG specification.
+#[allow(clippy::too_many_arguments)]
+fn flatten_arc(
+    polygon: &mut Vec<Point2<f32>>,
+    from: Point2<f32>,
+    radii: Vector2<f32>,
+    x_axis_rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: Point2<f32>,
+) {
+    let (mut rx, mut ry) = (radii.x.abs(), radii.y.abs());
+    if rx == 0. || ry == 0. || from == to {
+        polygon.push(to);
+        return;
+    }
+
+    let (sin_phi, cos_phi) = x_axis_rotation.to_radians().sin_cos();
+    let half_diff = (from - to) / 2.;
+    let x1p = cos_phi * half_diff.x + sin_phi * half_diff.y;
+    let y1p = -sin_phi * half_diff.x + cos_phi * half_diff.y;
+
+    // radii that are too small to reach between the endpoints are scaled up until they just fit
+    let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+    if lambda > 1. {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+    let denominator = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+    let mut coefficient = (numerator / denominator).max(0.).sqrt();
+    if large_arc == sweep {
+        coefficient = -coefficient;
+    }
+    let cxp = coefficient * rx * y1p / ry;
+    let cyp = -coefficient * ry * x1p / rx;
+    let center = na::center(&from, &to)
+        + Vector2::new(cos_phi * cxp - sin_phi * cyp, sin_phi * cxp + cos_phi * cyp);
+
+    let angle = |x: f32, y: f32| y.atan2(x);
+    let start_angle = angle((x1p - cxp) / rx, (y1p - cyp) / ry);
+    let mut sweep_angle = angle((-x1p - cxp) / rx, (-y1p - cyp) / ry) - start_angle;
+    if sweep && sweep_angle < 0. {
+        sweep_angle += f32::consts::TAU;
+    } else if !sweep && sweep_angle > 0. {
+        sweep_angle -= f32::consts::TAU;
+    }
+
+    let segments = get_segment_count(sweep_angle.abs() * rx.max(ry));
+    for i in 1..=segments {
+        if i == segments {
+            polygon.push(to);
+            break;
+        }
+        let theta = start_angle + sweep_angle * i as f32 / segments as f32;
+        let (x, y) = (rx * theta.cos(), ry * theta.sin());
+        polygon.push(center + Vector2::new(cos_phi * x - sin_phi * y, sin_phi * x + cos_phi * y));
+    }
+}
+
+/// Parses SVG path data into a list of flattened polygons, one for each subpath.
+pub fn parse_svg_path(d: &str) -> Result<Vec<Vec<Point2<f32>>>, String> {
+    let mut tokenizer = PathTokenizer {
+        data: d.as_bytes(),
+        pos: 0,
+    };
+    let mut polygons: Vec<Vec<Point2<f32>>> = Vec::new();
+    let mut polygon: Vec<Point2<f32>> = Vec::new();
+    let mut current = p2(0., 0.);
+    let mut subpath_start = current;
+    // The second control point of the previous curve, used to reflect for `S` and `T` commands
+    let mut last_cubic_control: Option<Point2<f32>> = None;
+    let mut last_quadratic_control: Option<Point2<f32>> = None;
+    let mut command: Option<u8> = None;
+
+    while !tokenizer.is_done() {
+        if let Some(next_command) = tokenizer.peek_command() {
+            tokenizer.pos += 1;
+            command = Some(next_command);
+        } else if !tokenizer.has_number() {
+            return Err(format!(
+                "Unexpected character at position {} of path data",
+                tokenizer.pos
+            ));
+        }
+        let command = command.ok_or_else(|| "Path data must start with a command".to_string())?;
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative {
+            current.coords
+        } else {
+            Vector2::zeros()
+        };
+        let (cubic_control, quadratic_control) = (last_cubic_control, last_quadratic_control);
+        last_cubic_control = None;
+        last_quadratic_control = None;
+
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                if polygon.len() > 1 {
+                    polygons.push(std::mem::take(&mut polygon));
+                }
+                polygon.clear();
+                current = Point2::from(origin + tokenizer.point()?);
+                subpath_start = current;
+                polygon.push(current);
+                // coordinates following a moveto without a new command are implicit linetos
+                while tokenizer.has_number() {
+                    let origin = if relative {
+                        current.coords
+                    } else {
+                        Vector2::zeros()
+                    };
+                    current = Point2::from(origin + tokenizer.point()?);
+                    polygon.push(current);
+                }
+            }
+            b'L' => {
+                current = Point2::from(origin + tokenizer.point()?);
+                polygon.push(current);
+            }
+            b'H' => {
+                current.x = origin.x + tokenizer.number()?;
+                polygon.push(current);
+            }
+            b'V' => {
+                current.y = origin.y + tokenizer.number()?;
+                polygon.push(current);
+            }
+            b'C' => {
+                let c1 = Point2::from(origin + tokenizer.point()?);
+                let c2 = Point2::from(origin + tokenizer.point()?);
+                let to = Point2::from(origin + tokenizer.point()?);
+                flatten_cubic(&mut polygon, current, c1, c2, to);
+                last_cubic_control = Some(c2);
+                current = to;
+            }
+            b'S' => {
+                let c1 = cubic_control.map_or(current, |control| current + (current - control));
+                let c2 = Point2::from(origin + tokenizer.point()?);
+                let to = Point2::from(origin + tokenizer.point()?);
+                flatten_cubic(&mut polygon, current, c1, c2, to);
+                last_cubic_control = Some(c2);
+                current = to;
+            }
+            b'Q' => {
+                let control = Point2::from(origin + tokenizer.point()?);
+                let to = Point2::from(origin + tokenizer.point()?);
+                flatten_quadratic(&mut polygon, current, control, to);
+                last_quadratic_control = Some(control);
+                current = to;
+            }
+            b'T' => {
+                let control =
+                    quadratic_control.map_or(current, |control| current + (current - control));
+                let to = Point2::from(origin + tokenizer.point()?);
+                flatten_quadratic(&mut polygon, current, control, to);
+                last_quadratic_control = Some(control);
+                current = to;
+            }
+            b'A' => {
+                let radii = tokenizer.point()?;
+                let x_axis_rotation = tokenizer.number()?;
+                let large_arc = tokenizer.flag()?;
+                let sweep = tokenizer.flag()?;
+                let to = Point2::from(origin + tokenizer.point()?);
+                flatten_arc(
+                    &mut polygon,
+                    current,
+                    radii,
+                    x_axis_rotation,
+                    large_arc,
+                    sweep,
+                    to,
+                );
+                current = to;
+            }
+            b'Z' => {
+                if polygon.len() > 1 {
+                    polygons.push(std::mem::take(&mut polygon));
+                }
+                // drawing can continue from the start of the closed subpath
+                current = subpath_start;
+                polygon.push(current);
+                // `Z` takes no arguments, so it can't be repeated implicitly
+                if tokenizer.has_number() {
+                    return Err(format!(
+                        "Unexpected number after closepath at position {} of path data",
+                        tokenizer.pos
+                    ));
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "Unsupported path command '{}' in path data",
+                    command as char
+                ))
+            }
+        }
+    }
+    if polygon.len() > 1 {
+        polygons.push(polygon);
+    }
+
+    Ok(polygons)
+}
+
+#[test]
+fn svg_path_parsing() {
+    // Square with a square hole, written with a mix of absolute/relative commands and no separators
+    let d = "M0,0H100V100H0Z m25 25v50h50v-50z";
+    let polygons = parse_svg_path(d).unwrap();
+    assert_eq!(polygons.len(), 2);
+    assert_eq!(polygons[1][2], p2(75., 75.));
+
+    let even_odd = Mask::new(polygons.clone(), FillRule::EvenOdd);
+    assert!(even_odd.contains_point(p2(10., 10.)));
+    assert!(!even_odd.contains_point(p2(50., 50.)));
+    assert!(!even_odd.contains_point(p2(150., 50.)));
+    // the subpaths wind in opposite directions, so the hole is only filled with the nonzero rule
+    // once the inner one is reversed
+    let non_zero = Mask::new(polygons, FillRule::NonZero);
+    assert!(!non_zero.contains_point(p2(50., 50.)));
+    let same_winding = parse_svg_path("M0 0H100V100H0Z M25 25H75V75H25Z").unwrap();
+    assert!(Mask::new(same_winding, FillRule::NonZero).contains_point(p2(50., 50.)));
+
+    let triangle = [p2(5., 5.), p2(20., 5.), p2(5., 20.)];
+    assert!(even_odd.contains_triangle(&triangle));
+    let crossing_hole = [p2(5., 5.), p2(95., 5.), p2(50., 95.)];
+    assert!(!even_odd.contains_triangle(&crossing_hole));
+    assert!(even_odd.intersects_triangle(&crossing_hole));
+
+    // Curves and arcs end exactly at their endpoints
+    let circle = parse_svg_path(
+        "M10 50a40 40 0 1 0 80 0A40 40 0 1 0 10 50zM50 50q10-10 20 0t20 0c0-5 5-5 5 0s-5 5-5 5",
+    )
+    .unwrap();
+    assert_eq!(circle.len(), 2);
+    assert!(circle[0]
+        .iter()
+        .all(|pt| ((pt - p2(50., 50.)).norm() - 40.).abs() < 0.01));
+    assert_eq!(*circle[1].last().unwrap(), p2(90., 55.));
+    assert!(parse_svg_path("M0 0 L10").is_err());
+    assert!(parse_svg_path("10 10").is_err());
+}
+
+#[test]
+fn shape_masks() {
+    let circle = Mask::ellipse(p2(50., 50.), Vector2::new(40., 40.));
+    assert!(circle.contains_point(p2(50., 50.)));
+    assert!(circle.contains_point(p2(50., 11.)));
+    assert!(!circle.contains_point(p2(15., 15.)));
+    assert!(circle.polygons[0]
+        .iter()
+        .all(|pt| ((pt - p2(50., 50.)).norm() - 40.).abs() < 0.01));
+
+    let ellipse = Mask::ellipse(p2(0., 0.), Vector2::new(100., 10.));
+    assert!(ellipse.contains_point(p2(90., 0.)));
+    assert!(!ellipse.contains_point(p2(0., 15.)));
+
+    let rounded = Mask::rounded_rect(&AABB::new(p2(0., 0.), p2(100., 50.)), 20.);
+    assert!(rounded.contains_point(p2(50., 1.)));
+    assert!(rounded.contains_point(p2(99., 25.)));
+    assert!(!rounded.contains_point(p2(2., 2.)));
+    assert!(!rounded.contains_point(p2(98., 48.)));
+    let square = Mask::rounded_rect(&AABB::new(p2(0., 0.), p2(10., 10.)), 0.);
+    assert_eq!(square.polygons[0].len(), 4);
+    assert!(square.contains_point(p2(1., 9.)));
+
+    // clockwise and counterclockwise polygons are both filled
+    let polygon = Mask::polygon(vec![p2(0., 0.), p2(0., 10.), p2(10., 0.)]);
+    assert!(polygon.contains_point(p2(2., 2.)));
+    assert!(!polygon.contains_point(p2(8., 8.)));
+}
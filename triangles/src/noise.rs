@@ -0,0 +1,121 @@
+//! Seeded gradient noise used to build flow fields that steer triangle growth
+
+use rand::seq::SliceRandom;
+use rand_core::SeedableRng;
+use rand_pcg::Pcg32;
+
+/// Gradient directions for 3D improved Perlin noise; the edges of a cube.
+const GRADIENTS: [(f32, f32, f32); 16] = [
+    (1., 1., 0.),
+    (-1., 1., 0.),
+    (1., -1., 0.),
+    (-1., -1., 0.),
+    (1., 0., 1.),
+    (-1., 0., 1.),
+    (1., 0., -1.),
+    (-1., 0., -1.),
+    (0., 1., 1.),
+    (0., -1., 1.),
+    (0., 1., -1.),
+    (0., -1., -1.),
+    (1., 1., 0.),
+    (0., -1., 1.),
+    (-1., 1., 0.),
+    (0., -1., -1.),
+];
+
+/// Ken Perlin's "improved" gradient noise with a permutation table generated from a seed.  The
+/// third dimension is used for time so that 2D fields can evolve smoothly.
+#[derive(Clone)]
+pub struct PerlinNoise {
+    perm: [u8; 512],
+}
+
+#[inline]
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+#[inline]
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+#[inline]
+fn grad(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+    let (gx, gy, gz) = GRADIENTS[(hash & 15) as usize];
+    gx * x + gy * y + gz * z
+}
+
+impl PerlinNoise {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Pcg32::seed_from_u64(seed);
+        let mut table: [u8; 256] = [0; 256];
+        for (i, val) in table.iter_mut().enumerate() {
+            *val = i as u8;
+        }
+        table.shuffle(&mut rng);
+
+        let mut perm = [0; 512];
+        for (i, val) in perm.iter_mut().enumerate() {
+            *val = table[i & 255];
+        }
+        PerlinNoise { perm }
+    }
+
+    #[inline]
+    fn hash(&self, x: usize, y: usize, z: usize) -> u8 {
+        let perm = &self.perm;
+        perm[perm[perm[x] as usize + y] as usize + z]
+    }
+
+    /// Samples the noise at the given point, returning a value roughly in the range `[-1, 1]`.
+    pub fn sample(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+        let (xi, yi, zi) = (
+            (x0 as i32 & 255) as usize,
+            (y0 as i32 & 255) as usize,
+            (z0 as i32 & 255) as usize,
+        );
+        let (x, y, z) = (x - x0, y - y0, z - z0);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let corner = |dx: usize, dy: usize, dz: usize| {
+            grad(
+                self.hash(xi + dx, yi + dy, zi + dz),
+                x - dx as f32,
+                y - dy as f32,
+                z - dz as f32,
+            )
+        };
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, corner(0, 0, 0), corner(1, 0, 0)),
+                lerp(u, corner(0, 1, 0), corner(1, 1, 0)),
+            ),
+            lerp(
+                v,
+                lerp(u, corner(0, 0, 1), corner(1, 0, 1)),
+                lerp(u, corner(0, 1, 1), corner(1, 1, 1)),
+            ),
+        )
+    }
+}
+
+#[test]
+fn noise_is_deterministic_and_bounded() {
+    let noise1 = PerlinNoise::new(9209);
+    let noise2 = PerlinNoise::new(9209);
+
+    for i in 0..200 {
+        let (x, y, z) = (i as f32 * 0.37, i as f32 * 0.91, i as f32 * 0.13);
+        let val = noise1.sample(x, y, z);
+        assert_eq!(val, noise2.sample(x, y, z));
+        assert!((-1.5..=1.5).contains(&val));
+    }
+    // Noise is zero at lattice points
+    assert_eq!(noise1.sample(3., 4., 5.), 0.);
+}
@@ -0,0 +1,290 @@
+//! Robinson triangle geometry and matching rules for growing Penrose rhombus (P3) tilings.
+//!
+//! Each rhombus is made of two Robinson triangles joined along their bases: thin rhombi are two
+//! acute (36-72-72) triangles and thick rhombi are two obtuse (108-36-36) triangles.  Triangles are
+//! stored with their vertices ordered as `[A, B, C]` where `A` is the apex, so the kind and
+//! handedness of any triangle can be recovered from its geometry alone.
+//!
+//! The matching rules were derived from the edges shared between triangles in a tiling generated by
+//! repeated deflation, so any tiling assembled following them is locally identical to a real Penrose
+//! tiling.
+
+use ncollide2d::na::{self, Isometry2, Point2, Vector2};
+
+use super::TriangleBuf;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RobinsonKind {
+    /// Half of a thin rhombus with a 36° apex
+    Acute,
+    /// Half of a thick rhombus with a 108° apex
+    Obtuse,
+}
+
+impl RobinsonKind {
+    pub fn get_apex_angle(self) -> f32 {
+        match self {
+            RobinsonKind::Acute => 36f32.to_radians(),
+            RobinsonKind::Obtuse => 108f32.to_radians(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RobinsonEdge {
+    AB,
+    AC,
+    /// The base, which is the diagonal of the rhombus that the triangle is half of
+    BC,
+}
+
+pub const EDGES: [RobinsonEdge; 3] = [RobinsonEdge::AB, RobinsonEdge::AC, RobinsonEdge::BC];
+
+impl RobinsonEdge {
+    /// Returns the `(first, second)` endpoints of this edge of `triangle`
+    pub fn get_endpoints(self, triangle: &TriangleBuf) -> (Point2<f32>, Point2<f32>) {
+        let [a, b, c] = *triangle;
+        match self {
+            RobinsonEdge::AB => (a, b),
+            RobinsonEdge::AC => (a, c),
+            RobinsonEdge::BC => (b, c),
+        }
+    }
+}
+
+/// A triangle of kind `kind` and handedness `ccw` may share its edge `edge` with the edge
+/// `other_edge` of a triangle of kind `other_kind` and handedness `other_ccw`.  If `aligned` is set,
+/// the first endpoints of the two edges coincide; otherwise the first endpoint of each coincides with
+/// the second endpoint of the other.
+struct MatchingRule {
+    kind: RobinsonKind,
+    ccw: bool,
+    edge: RobinsonEdge,
+    other_kind: RobinsonKind,
+    other_ccw: bool,
+    other_edge: RobinsonEdge,
+    aligned: bool,
+}
+
+const fn rule(
+    kind: RobinsonKind,
+    ccw: bool,
+    edge: RobinsonEdge,
+    other_kind: RobinsonKind,
+    other_ccw: bool,
+    other_edge: RobinsonEdge,
+    aligned: bool,
+) -> MatchingRule {
+    MatchingRule {
+        kind,
+        ccw,
+        edge,
+        other_kind,
+        other_ccw,
+        other_edge,
+        aligned,
+    }
+}
+
+use RobinsonEdge::{AB, AC, BC};
+use RobinsonKind::{Acute, Obtuse};
+
+const MATCHING_RULES: [MatchingRule; 18] = [
+    rule(Acute, false, AB, Obtuse, false, AB, false),
+    rule(Acute, false, AC, Acute, true, AC, true),
+    rule(Acute, false, AC, Obtuse, true, AC, true),
+    rule(Acute, false, BC, Acute, true, BC, true),
+    rule(Acute, true, AB, Obtuse, true, AB, false),
+    rule(Acute, true, AC, Acute, false, AC, true),
+    rule(Acute, true, AC, Obtuse, false, AC, true),
+    rule(Acute, true, BC, Acute, false, BC, true),
+    rule(Obtuse, false, AB, Acute, false, AB, false),
+    rule(Obtuse, false, AB, Obtuse, true, AB, true),
+    rule(Obtuse, false, AC, Acute, true, AC, true),
+    rule(Obtuse, false, AC, Obtuse, true, AC, true),
+    rule(Obtuse, false, BC, Obtuse, true, BC, true),
+    rule(Obtuse, true, AB, Acute, true, AB, false),
+    rule(Obtuse, true, AB, Obtuse, false, AB, true),
+    rule(Obtuse, true, AC, Acute, false, AC, true),
+    rule(Obtuse, true, AC, Obtuse, false, AC, true),
+    rule(Obtuse, true, BC, Obtuse, false, BC, true),
+];
+
+#[inline]
+fn cross(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Returns the kind and handedness of a Robinson triangle stored as `[A, B, C]`
+pub fn classify(triangle: &TriangleBuf) -> (RobinsonKind, bool) {
+    let [a, b, c] = *triangle;
+    let kind = if (b - a).dot(&(c - a)) > 0. {
+        RobinsonKind::Acute
+    } else {
+        RobinsonKind::Obtuse
+    };
+    (kind, cross(b - a, c - a) > 0.)
+}
+
+/// Builds a Robinson triangle with legs of length `leg_length` whose edge `edge` runs from `first`
+/// to `second`.
+pub fn build_triangle(
+    kind: RobinsonKind,
+    ccw: bool,
+    edge: RobinsonEdge,
+    first: Point2<f32>,
+    second: Point2<f32>,
+    leg_length: f32,
+) -> TriangleBuf {
+    let angle = if ccw {
+        kind.get_apex_angle()
+    } else {
+        -kind.get_apex_angle()
+    };
+    match edge {
+        RobinsonEdge::AB => [
+            first,
+            second,
+            first + Isometry2::rotation(angle) * (second - first),
+        ],
+        RobinsonEdge::AC => [
+            first,
+            first + Isometry2::rotation(-angle) * (second - first),
+            second,
+        ],
+        RobinsonEdge::BC => {
+            let base = second - first;
+            let half_base = base.norm() / 2.;
+            let height = (leg_length * leg_length - half_base * half_base)
+                .max(0.)
+                .sqrt();
+            let normal = Vector2::new(-base.y, base.x).normalize();
+            let midpoint = na::center(&first, &second);
+            let apex = midpoint + normal * height;
+            if (cross(first - apex, second - apex) > 0.) == ccw {
+                [apex, first, second]
+            } else {
+                [midpoint - normal * height, first, second]
+            }
+        }
+    }
+}
+
+/// Returns the seed triangle that a Penrose chain grows from: an acute triangle with its apex at
+/// `origin` rotated by `rotation`
+pub fn build_seed_triangle(origin: Point2<f32>, rotation: f32, leg_length: f32) -> TriangleBuf {
+    let b = origin + Isometry2::rotation(rotation) * Vector2::new(leg_length, 0.);
+    build_triangle(
+        RobinsonKind::Acute,
+        true,
+        RobinsonEdge::AB,
+        origin,
+        b,
+        leg_length,
+    )
+}
+
+/// Returns all triangles that could be placed sharing the edge `edge` of `triangle` without
+/// violating the matching rules, along with the edge of the new triangle that is shared.
+pub fn get_matching_neighbors(
+    triangle: &TriangleBuf,
+    edge: RobinsonEdge,
+    leg_length: f32,
+) -> Vec<(TriangleBuf, RobinsonEdge)> {
+    let (kind, ccw) = classify(triangle);
+    let (first, second) = edge.get_endpoints(triangle);
+    MATCHING_RULES
+        .iter()
+        .filter(|rule| rule.kind == kind && rule.ccw == ccw && rule.edge == edge)
+        .map(|rule| {
+            let (other_first, other_second) = if rule.aligned {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            let neighbor = build_triangle(
+                rule.other_kind,
+                rule.other_ccw,
+                rule.other_edge,
+                other_first,
+                other_second,
+                leg_length,
+            );
+            (neighbor, rule.other_edge)
+        })
+        .collect()
+}
+
+/// If some edge of `triangle` coincides with some edge of `other` (within `epsilon`), returns
+/// `Some(true)` if the two triangles are allowed to share it by the matching rules and `Some(false)`
+/// if not.  Returns `None` if they don't share an edge.
+pub fn check_shared_edge(
+    triangle: &TriangleBuf,
+    other: &TriangleBuf,
+    epsilon: f32,
+) -> Option<bool> {
+    let close = |a: Point2<f32>, b: Point2<f32>| (a - b).norm() < epsilon;
+    let (kind, ccw) = classify(triangle);
+    let (other_kind, other_ccw) = classify(other);
+
+    for &edge in &EDGES {
+        let (first, second) = edge.get_endpoints(triangle);
+        for &other_edge in &EDGES {
+            let (other_first, other_second) = other_edge.get_endpoints(other);
+            let aligned = if close(first, other_first) && close(second, other_second) {
+                true
+            } else if close(first, other_second) && close(second, other_first) {
+                false
+            } else {
+                continue;
+            };
+
+            return Some(MATCHING_RULES.iter().any(|rule| {
+                rule.kind == kind
+                    && rule.ccw == ccw
+                    && rule.edge == edge
+                    && rule.other_kind == other_kind
+                    && rule.other_ccw == other_ccw
+                    && rule.other_edge == other_edge
+                    && rule.aligned == aligned
+            }));
+        }
+    }
+
+    None
+}
+
+#[test]
+fn matching_neighbors_are_consistent() {
+    let seed = build_seed_triangle(super::p2(100., 100.), 0.4, 20.);
+    assert_eq!(classify(&seed), (RobinsonKind::Acute, true));
+
+    for &edge in &EDGES {
+        let neighbors = get_matching_neighbors(&seed, edge, 20.);
+        assert!(!neighbors.is_empty());
+        for (neighbor, _) in neighbors {
+            // Matching is symmetric and neighbors are built on the far side of the shared edge
+            assert_eq!(check_shared_edge(&neighbor, &seed, 0.001), Some(true));
+            assert_eq!(check_shared_edge(&seed, &neighbor, 0.001), Some(true));
+            let (first, second) = edge.get_endpoints(&seed);
+            let side = |pt: Point2<f32>| cross(second - first, pt - first);
+            let seed_apex = seed.iter().find(|pt| side(**pt).abs() > 0.001).unwrap();
+            let neighbor_apex = neighbor.iter().find(|pt| side(**pt).abs() > 0.001).unwrap();
+            assert!(side(*seed_apex) * side(*neighbor_apex) < 0.);
+            assert!(neighbor
+                .iter()
+                .all(|pt| (pt - neighbor[0]).norm() < 20. * 1.7));
+        }
+    }
+
+    // Acute triangles can only share a leg with triangles whose apex is at the same vertex
+    let reversed = build_triangle(
+        RobinsonKind::Acute,
+        true,
+        RobinsonEdge::AC,
+        seed[2],
+        seed[0],
+        20.,
+    );
+    assert_eq!(check_shared_edge(&seed, &reversed, 0.001), Some(false));
+}
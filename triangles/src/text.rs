@@ -0,0 +1,254 @@
+//! Converts text set in a TrueType/OpenType font into a mask so that triangles can grow in the
+//! shape of the text or be kept out of it.
+
+use ncollide2d::bounding_volume::aabb::AABB;
+use ncollide2d::na::Point2;
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
+
+use super::mask::{flatten_cubic, flatten_quadratic, FillRule, Mask};
+use super::p2;
+
+/// Collects the outline of a glyph as flattened polygons, transforming it from font units (with y
+/// pointing up) into canvas coordinates with the glyph's origin at `origin`.
+struct GlyphOutliner<'a> {
+    polygons: &'a mut Vec<Vec<Point2<f32>>>,
+    polygon: Vec<Point2<f32>>,
+    origin: Point2<f32>,
+    scale: f32,
+}
+
+impl<'a> GlyphOutliner<'a> {
+    fn transform(&self, x: f32, y: f32) -> Point2<f32> {
+        p2(
+            self.origin.x + x * self.scale,
+            self.origin.y - y * self.scale,
+        )
+    }
+
+    fn last_point(&self) -> Point2<f32> {
+        self.polygon.last().copied().unwrap_or(self.origin)
+    }
+
+    fn finish_polygon(&mut self) {
+        let polygon = std::mem::take(&mut self.polygon);
+        if polygon.len() > 2 {
+            self.polygons.push(polygon);
+        }
+    }
+}
+
+impl<'a> OutlineBuilder for GlyphOutliner<'a> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.finish_polygon();
+        let pt = self.transform(x, y);
+        self.polygon.push(pt);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let pt = self.transform(x, y);
+        self.polygon.push(pt);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (from, control, to) = (
+            self.last_point(),
+            self.transform(x1, y1),
+            self.transform(x, y),
+        );
+        flatten_quadratic(&mut self.polygon, from, control, to);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (from, control_1, control_2, to) = (
+            self.last_point(),
+            self.transform(x1, y1),
+            self.transform(x2, y2),
+            self.transform(x, y),
+        );
+        flatten_cubic(&mut self.polygon, from, control_1, control_2, to);
+    }
+
+    fn close(&mut self) {
+        self.finish_polygon();
+    }
+}
+
+/// Lays out `text` using the font in `font_bytes`, scaled as large as possible while fitting inside
+/// of `layout` and centered in it, and returns a mask covering the glyphs.  Lines are separated by
+/// `\n` and each one is centered horizontally.
+pub fn build_text_mask(text: &str, font_bytes: &[u8], layout: &AABB<f32>) -> Result<Mask, String> {
+    let face =
+        Face::parse(font_bytes, 0).map_err(|err| format!("Failed to parse font: {}", err))?;
+    let lines: Vec<Vec<GlyphId>> = text
+        .lines()
+        .map(|line| {
+            line.chars()
+                .map(|c| face.glyph_index(c).unwrap_or(GlyphId(0)))
+                .collect()
+        })
+        .collect();
+    let get_advance = |glyph_id: GlyphId| face.glyph_hor_advance(glyph_id).unwrap_or(0) as f32;
+    let line_widths: Vec<f32> = lines
+        .iter()
+        .map(|line| line.iter().copied().map(get_advance).sum())
+        .collect();
+
+    let (ascender, descender) = (face.ascender() as f32, face.descender() as f32);
+    let line_height = ascender - descender + face.line_gap() as f32;
+    let text_width = line_widths.iter().copied().fold(0., f32::max);
+    let text_height = line_height * lines.len().saturating_sub(1) as f32 + ascender - descender;
+    if text_width <= 0. || text_height <= 0. {
+        return Err("Text has no glyphs to lay out".into());
+    }
+
+    let (layout_width, layout_height) =
+        (layout.maxs.x - layout.mins.x, layout.maxs.y - layout.mins.y);
+    let scale = (layout_width / text_width).min(layout_height / text_height);
+    let top = layout.mins.y + (layout_height - text_height * scale) / 2.;
+
+    let mut polygons = Vec::new();
+    for (line_ix, (line, line_width)) in lines.iter().zip(&line_widths).enumerate() {
+        let baseline = top + (ascender + line_height * line_ix as f32) * scale;
+        let mut pen_x = layout.mins.x + (layout_width - line_width * scale) / 2.;
+        for &glyph_id in line {
+            let mut outliner = GlyphOutliner {
+                polygons: &mut polygons,
+                polygon: Vec::new(),
+                origin: p2(pen_x, baseline),
+                scale,
+            };
+            face.outline_glyph(glyph_id, &mut outliner);
+            outliner.finish_polygon();
+            pen_x += get_advance(glyph_id) * scale;
+        }
+    }
+
+    if polygons.is_empty() {
+        return Err("Text has no glyph outlines to fill".into());
+    }
+    // glyph outlines are designed to be filled with the nonzero rule
+    Ok(Mask::new(polygons, FillRule::NonZero))
+}
+
+/// Builds a minimal TrueType font with an ascender of 800 and a descender of -200 units.  "I" is a
+/// bar 200 units wide and 700 tall, centered in its advance of 400, while the space and the
+/// `.notdef` glyph that every other character maps to have no outlines.
+#[cfg(test)]
+fn build_test_font() -> Vec<u8> {
+    fn be16(table: &mut Vec<u8>, values: &[i32]) {
+        for &value in values {
+            table.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+    }
+
+    // glyphs are `.notdef`, space and "I", which has one contour of on-curve points given as
+    // offsets from the previous point
+    let mut glyf = Vec::new();
+    be16(&mut glyf, &[1, 100, 0, 300, 700, 3, 0]);
+    glyf.extend_from_slice(&[1; 4]);
+    be16(&mut glyf, &[100, 0, 200, 0, 0, 700, 0, -700]);
+    let mut loca = Vec::new();
+    be16(&mut loca, &[0, 0, 0, glyf.len() as i32 / 2]);
+    let mut hmtx = Vec::new();
+    be16(&mut hmtx, &[500, 0, 250, 0, 400, 100]);
+    let mut head = Vec::new();
+    be16(&mut head, &[1, 0, 1, 0, 0, 0, 0x5F0F, 0x3CF5, 0, 1000]);
+    be16(&mut head, &[0; 8]);
+    be16(&mut head, &[100, 0, 300, 700, 0, 8, 2, 0, 0]);
+    let mut hhea = Vec::new();
+    be16(&mut hhea, &[1, 0, 800, -200, 0, 500, 0, 0, 300, 1, 0, 0]);
+    be16(&mut hhea, &[0, 0, 0, 0, 0, 3]);
+    let mut maxp = Vec::new();
+    be16(&mut maxp, &[0, 0x5000, 3]);
+    // a format 4 subtable mapping " " and "I" to their glyphs, ending with the required 0xFFFF
+    let mut cmap = Vec::new();
+    be16(&mut cmap, &[0, 1, 3, 1, 0, 12]);
+    be16(&mut cmap, &[4, 40, 0, 6, 4, 1, 2]);
+    be16(&mut cmap, &[0x20, 0x49, 0xFFFF, 0, 0x20, 0x49, 0xFFFF]);
+    be16(&mut cmap, &[1 - 0x20, 2 - 0x49, 1, 0, 0, 0]);
+
+    let tables = [
+        (b"cmap", cmap),
+        (b"glyf", glyf),
+        (b"head", head),
+        (b"hhea", hhea),
+        (b"hmtx", hmtx),
+        (b"loca", loca),
+        (b"maxp", maxp),
+    ];
+    let mut font = Vec::new();
+    be16(&mut font, &[1, 0, tables.len() as i32, 64, 2, 48]);
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, table) in &tables {
+        font.extend_from_slice(*tag);
+        font.extend_from_slice(&0u32.to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += table.len().next_multiple_of(4);
+    }
+    for (_, table) in &tables {
+        font.extend_from_slice(table);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+    font
+}
+
+#[test]
+fn glyph_outlines_are_transformed_and_flattened() {
+    let mut polygons = Vec::new();
+    let mut outliner = GlyphOutliner {
+        polygons: &mut polygons,
+        polygon: Vec::new(),
+        origin: p2(100., 200.),
+        scale: 0.5,
+    };
+    // a square with a rounded top, then an unclosed degenerate contour that is dropped
+    outliner.move_to(0., 0.);
+    outliner.line_to(100., 0.);
+    outliner.line_to(100., 100.);
+    outliner.quad_to(50., 150., 0., 100.);
+    outliner.close();
+    outliner.move_to(0., 0.);
+    outliner.line_to(10., 10.);
+    outliner.finish_polygon();
+
+    assert_eq!(polygons.len(), 1);
+    assert_eq!(
+        polygons[0][..3],
+        [p2(100., 200.), p2(150., 200.), p2(150., 150.)]
+    );
+    assert_eq!(*polygons[0].last().unwrap(), p2(100., 150.));
+    assert!(polygons[0].len() > 5);
+
+    let mask = Mask::new(polygons, FillRule::NonZero);
+    assert!(mask.contains_point(p2(125., 140.)));
+    assert!(!mask.contains_point(p2(125., 210.)));
+    assert!(build_text_mask("a", &[0, 1, 2], &mask.bounds).is_err());
+}
+
+#[test]
+fn text_is_laid_out_from_glyph_outlines() {
+    let font = build_test_font();
+
+    // "II" is 800 by 1000 units, so it is scaled by 0.1 to fit and its baseline ends up at y = 80
+    let layout = AABB::new(p2(0., 0.), p2(80., 100.));
+    let mask = build_text_mask("II", &font, &layout).unwrap();
+    assert_eq!(mask.polygons.len(), 2);
+    assert!(mask.contains_point(p2(20., 50.)));
+    assert!(mask.contains_point(p2(60., 50.)));
+    assert!(!mask.contains_point(p2(40., 50.)));
+    assert!(!mask.contains_point(p2(20., 85.)));
+    assert!(!mask.contains_point(p2(20., 5.)));
+
+    // lines are stacked and centered horizontally
+    let layout = AABB::new(p2(0., 0.), p2(80., 200.));
+    let mask = build_text_mask("II\nI", &font, &layout).unwrap();
+    assert_eq!(mask.polygons.len(), 3);
+    assert!(mask.contains_point(p2(40., 150.)));
+    assert!(!mask.contains_point(p2(20., 150.)));
+
+    // whitespace and characters without glyphs have nothing to fill
+    for text in &[" ", "  \n ", "\u{e9}"] {
+        assert!(build_text_mask(text, &font, &layout).is_err());
+    }
+}